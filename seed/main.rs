@@ -4,7 +4,7 @@ use bson::doc;
 use chrono::Utc;
 use ticketing_app::{
     config::Config,
-    models::{admin::Admin, event::Event},
+    models::{admin::{Admin, AdminRole}, event::{EntryRules, Event, EventStatus, EventVisibility, ReentryPolicy, TicketDelivery, TicketLayout, TicketTier}},
     utils::auth::hash_password,
 };
 
@@ -16,7 +16,7 @@ async fn main() -> mongodb::error::Result<()> {
     let mut client_options = ClientOptions::parse(&config.mongodb_uri).await?;
     client_options.app_name = Some("ticketing_app_seed".to_string());
     let client = Client::with_options(client_options)?;
-    let db_name = config.mongodb_uri.split('/').last().unwrap_or("ticketing_db");
+    let db_name = config.mongodb_uri.split('/').next_back().unwrap_or("ticketing_db");
     let db = client.database(db_name);
 
     // Seed Admin
@@ -27,6 +27,7 @@ async fn main() -> mongodb::error::Result<()> {
             id: None,
             email: config.admin_email.clone(),
             password_hash,
+            role: AdminRole::Admin,
             created_at: Utc::now(),
         };
         admin_collection.insert_one(admin, None).await?;
@@ -45,22 +46,57 @@ async fn main() -> mongodb::error::Result<()> {
         Event {
             id: None,
             name: "Jakarta Music Fest".to_string(),
+            slug: "jakarta-music-fest".to_string(),
             description: "A night of stellar music performances.".to_string(),
             date: Utc::now() + chrono::Duration::days(30),
+            timezone: "Asia/Jakarta".to_string(),
             location: "Jakarta Convention Center".to_string(),
             price: 150000.0,
             total_tickets: 500,
             available_tickets: 500,
+            tiers: vec![
+                TicketTier { name: "regular".to_string(), price: 150000.0 },
+                TicketTier { name: "vip".to_string(), price: 350000.0 },
+            ],
+            category: Some("music".to_string()),
+            tags: vec!["concert".to_string(), "jakarta".to_string()],
+            poster: None,
+            gallery: Vec::new(),
+            visibility: EventVisibility::Public,
+            public_sale_starts_at: None,
+            status: EventStatus::Published,
+            ticket_layout: TicketLayout::default(),
+            entry_rules: EntryRules {
+                reentry: ReentryPolicy::Unlimited,
+                venue_capacity: Some(500),
+                ..EntryRules::default()
+            },
+            ticket_delivery: TicketDelivery::Auto,
+            updated_at: Some(Utc::now()),
         },
         Event {
             id: None,
             name: "Comedy Night".to_string(),
+            slug: "comedy-night".to_string(),
             description: "Get ready to laugh out loud with top comedians.".to_string(),
             date: Utc::now() + chrono::Duration::days(15),
+            timezone: "Asia/Jakarta".to_string(),
             location: "Isola Bar, Jakarta".to_string(),
             price: 75000.0,
             total_tickets: 200,
             available_tickets: 200,
+            tiers: Vec::new(),
+            category: Some("comedy".to_string()),
+            tags: vec!["standup".to_string(), "jakarta".to_string()],
+            poster: None,
+            gallery: Vec::new(),
+            visibility: EventVisibility::Public,
+            public_sale_starts_at: None,
+            status: EventStatus::Published,
+            ticket_layout: TicketLayout::default(),
+            entry_rules: EntryRules::default(),
+            ticket_delivery: TicketDelivery::Auto,
+            updated_at: Some(Utc::now()),
         },
     ];
 
@@ -10,7 +10,75 @@ pub struct Config {
     pub midtrans_server_key: String,
     pub midtrans_client_key: String,
     pub resend_api_key: String,
-    pub resend_from_email: String,
+    pub resend_webhook_secret: Option<String>,
+    pub mail_from: String,
+    pub mail_transport: String,
+    pub mail_dir: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_tls: String,
+    pub outbox_max_attempts: u32,
+    pub outbox_retry_base_secs: u64,
+    pub outbox_poll_secs: u64,
+    /// Jarak pengingat sebelum event dalam jam (dari `REMINDER_OFFSETS`, mis. `7d,1d,12h`)
+    pub reminder_offset_hours: Vec<u32>,
+    pub reminder_poll_secs: u64,
+    /// Menit sejak order dibuat sebelum email pemulihan checkout dikirim; 0 = mati
+    pub recovery_delay_minutes: i64,
+    pub recovery_poll_secs: u64,
+    /// Batas kirim ulang tiket oleh pembeli per jam
+    pub ticket_resend_email_limit: usize,
+    pub ticket_resend_ip_limit: usize,
+    /// Header IP klien yang ditulis reverse proxy tepercaya; kosong = alamat socket
+    pub trusted_ip_header: Option<String>,
+    pub notify_provider: String,
+    pub notify_dir: String,
+    pub phone_country_code: String,
+    pub twilio_account_sid: Option<String>,
+    pub twilio_auth_token: Option<String>,
+    pub twilio_sms_from: Option<String>,
+    pub twilio_whatsapp_from: Option<String>,
+    pub public_base_url: String,
+    pub storage_backend: String,
+    pub media_dir: String,
+    pub max_upload_bytes: u64,
+    pub ticket_signing_keys: String,
+    pub ticket_signing_key_id: Option<String>,
+    pub organization_name: String,
+    pub apple_pass_type_id: Option<String>,
+    pub apple_team_id: Option<String>,
+    pub apple_pass_cert_path: Option<String>,
+    pub apple_pass_key_path: Option<String>,
+    pub apple_pass_key_password: Option<String>,
+    pub apple_wwdr_cert_path: Option<String>,
+    pub google_wallet_issuer_id: Option<String>,
+    pub google_wallet_service_account_path: Option<String>,
+}
+
+fn optional_env(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
+// `7d` = 168 jam, `12h` = 12 jam; angka tanpa satuan dianggap jam. Kosong = pengingat mati.
+fn parse_offset_hours(value: &str) -> Vec<u32> {
+    let mut hours: Vec<u32> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            let parsed = match v.strip_suffix('d') {
+                Some(days) => days.parse::<u32>().map(|d| d * 24),
+                None => v.trim_end_matches('h').parse::<u32>(),
+            };
+            parsed.unwrap_or_else(|_| panic!("Invalid REMINDER_OFFSETS entry: {}", v))
+        })
+        .filter(|h| *h > 0)
+        .collect();
+    hours.sort_unstable_by(|a, b| b.cmp(a));
+    hours.dedup();
+    hours
 }
 
 impl Config {
@@ -22,8 +90,66 @@ impl Config {
             admin_password: env::var("ADMIN_PASSWORD").expect("ADMIN_PASSWORD must be set"),
             midtrans_server_key: env::var("MIDTRANS_SERVER_KEY").expect("MIDTRANS_SERVER_KEY must be set"),
             midtrans_client_key: env::var("MIDTRANS_CLIENT_KEY").expect("MIDTRANS_CLIENT_KEY must be set"),
-            resend_api_key: env::var("RESEND_API_KEY").expect("RESEND_API_KEY must be set"),
-            resend_from_email: env::var("RESEND_FROM_EMAIL").expect("RESEND_FROM_EMAIL must be set"),
+            resend_api_key: env::var("RESEND_API_KEY").unwrap_or_default(),
+            resend_webhook_secret: optional_env("RESEND_WEBHOOK_SECRET"),
+            mail_from: optional_env("MAIL_FROM")
+                .or_else(|| optional_env("RESEND_FROM_EMAIL"))
+                .expect("MAIL_FROM or RESEND_FROM_EMAIL must be set"),
+            mail_transport: env::var("MAIL_TRANSPORT").unwrap_or_else(|_| "resend".to_string()),
+            mail_dir: env::var("MAIL_DIR").unwrap_or_else(|_| "mail".to_string()),
+            smtp_host: optional_env("SMTP_HOST"),
+            smtp_port: env::var("SMTP_PORT").ok().and_then(|v| v.parse().ok()).unwrap_or(587),
+            smtp_username: optional_env("SMTP_USERNAME"),
+            smtp_password: optional_env("SMTP_PASSWORD"),
+            smtp_tls: env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string()),
+            outbox_max_attempts: env::var("OUTBOX_MAX_ATTEMPTS").ok().and_then(|v| v.parse().ok()).unwrap_or(6),
+            outbox_retry_base_secs: env::var("OUTBOX_RETRY_BASE_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(60),
+            outbox_poll_secs: env::var("OUTBOX_POLL_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(15),
+            reminder_offset_hours: parse_offset_hours(&env::var("REMINDER_OFFSETS").unwrap_or_else(|_| "7d,1d".to_string())),
+            reminder_poll_secs: env::var("REMINDER_POLL_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(300),
+            recovery_delay_minutes: env::var("RECOVERY_DELAY_MINUTES").ok().and_then(|v| v.parse().ok()).unwrap_or(60),
+            recovery_poll_secs: env::var("RECOVERY_POLL_SECONDS").ok().and_then(|v| v.parse().ok()).unwrap_or(300),
+            ticket_resend_email_limit: env::var("TICKET_RESEND_EMAIL_LIMIT").ok().and_then(|v| v.parse().ok()).unwrap_or(3),
+            ticket_resend_ip_limit: env::var("TICKET_RESEND_IP_LIMIT").ok().and_then(|v| v.parse().ok()).unwrap_or(10),
+            trusted_ip_header: optional_env("TRUSTED_IP_HEADER"),
+            notify_provider: env::var("NOTIFY_PROVIDER").unwrap_or_else(|_| "none".to_string()),
+            notify_dir: env::var("NOTIFY_DIR").unwrap_or_else(|_| "notifications".to_string()),
+            phone_country_code: env::var("PHONE_COUNTRY_CODE").unwrap_or_else(|_| "62".to_string()),
+            twilio_account_sid: optional_env("TWILIO_ACCOUNT_SID"),
+            twilio_auth_token: optional_env("TWILIO_AUTH_TOKEN"),
+            twilio_sms_from: optional_env("TWILIO_SMS_FROM"),
+            twilio_whatsapp_from: optional_env("TWILIO_WHATSAPP_FROM"),
+            public_base_url: env::var("PUBLIC_BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:8000".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            storage_backend: env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string()),
+            media_dir: env::var("MEDIA_DIR").unwrap_or_else(|_| "uploads".to_string()),
+            max_upload_bytes: env::var("MAX_UPLOAD_MB")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(5)
+                * 1024
+                * 1024,
+            ticket_signing_keys: env::var("TICKET_SIGNING_KEYS").unwrap_or_default(),
+            ticket_signing_key_id: optional_env("TICKET_SIGNING_KEY_ID"),
+            organization_name: env::var("ORGANIZATION_NAME").unwrap_or_else(|_| "Ticketing App".to_string()),
+            apple_pass_type_id: optional_env("APPLE_PASS_TYPE_ID"),
+            apple_team_id: optional_env("APPLE_TEAM_ID"),
+            apple_pass_cert_path: optional_env("APPLE_PASS_CERT"),
+            apple_pass_key_path: optional_env("APPLE_PASS_KEY"),
+            apple_pass_key_password: optional_env("APPLE_PASS_KEY_PASSWORD"),
+            apple_wwdr_cert_path: optional_env("APPLE_WWDR_CERT"),
+            google_wallet_issuer_id: optional_env("GOOGLE_WALLET_ISSUER_ID"),
+            google_wallet_service_account_path: optional_env("GOOGLE_WALLET_SERVICE_ACCOUNT"),
         }
     }
-}
\ No newline at end of file
+
+    pub fn public_host(&self) -> &str {
+        let without_scheme = self
+            .public_base_url
+            .split_once("://")
+            .map_or(self.public_base_url.as_str(), |(_, rest)| rest);
+        without_scheme.split('/').next().unwrap_or(without_scheme)
+    }
+}
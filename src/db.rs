@@ -1,10 +1,55 @@
 use mongodb::{Client, options::ClientOptions, Database};
+use mongodb::bson::doc;
+use mongodb::error::{ErrorKind, WriteFailure};
+use mongodb::options::IndexOptions;
+use mongodb::IndexModel;
 use crate::config::Config;
+use crate::models::event::Event;
+use crate::models::ticket::Ticket;
 
 pub async fn init_db(config: &Config) -> Database {
     let mut client_options = ClientOptions::parse(&config.mongodb_uri).await.unwrap();
     client_options.app_name = Some("ticketing_app".to_string());
 
     let client = Client::with_options(client_options).unwrap();
-    client.database(&config.mongodb_uri.split('/').last().unwrap_or("ticketing_db"))
-}
\ No newline at end of file
+    client.database(config.mongodb_uri.split('/').next_back().unwrap_or("ticketing_db"))
+}
+
+/// Index unik yang dipakai sebagai pengaman race condition (bukan sekadar performa).
+pub async fn ensure_indexes(db: &Database) -> mongodb::error::Result<()> {
+    // Tiket lama tanpa `seq` tidak ikut index
+    let ticket_seq = IndexModel::builder()
+        .keys(doc! {"order_id": 1, "seq": 1})
+        .options(
+            IndexOptions::builder()
+                .unique(true)
+                .partial_filter_expression(doc! {"seq": {"$exists": true}})
+                .build(),
+        )
+        .build();
+    db.collection::<Ticket>("tickets").create_index(ticket_seq, None).await?;
+
+    // Event lama dengan slug kosong tidak ikut index
+    let event_slug = IndexModel::builder()
+        .keys(doc! {"slug": 1})
+        .options(
+            IndexOptions::builder()
+                .unique(true)
+                .partial_filter_expression(doc! {"slug": {"$gt": ""}})
+                .build(),
+        )
+        .build();
+    db.collection::<Event>("events").create_index(event_slug, None).await?;
+    Ok(())
+}
+
+pub fn is_duplicate_key(error: &mongodb::error::Error) -> bool {
+    match error.kind.as_ref() {
+        ErrorKind::Write(WriteFailure::WriteError(e)) => e.code == 11000,
+        ErrorKind::BulkWrite(failure) => failure
+            .write_errors
+            .as_ref()
+            .is_some_and(|errors| errors.iter().any(|e| e.code == 11000)),
+        _ => false,
+    }
+}
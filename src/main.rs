@@ -1,6 +1,8 @@
 #[macro_use] extern crate rocket;
 
+use rocket::data::{ByteUnit, Limits};
 use rocket::fairing::{Fairing, Info, Kind};
+use rocket::fs::FileServer;
 use rocket::http::Header;
 use rocket::{Request, Response};
 
@@ -13,8 +15,19 @@ mod routes;
 mod utils;
 
 use config::Config;
-use db::init_db;
-use routes::{public, admin};
+use db::{ensure_indexes, init_db};
+use routes::{public, admin, staff};
+use utils::attendance::{AttendanceFeed, AttendanceStatsWorker};
+use utils::mailer::mailer_from_config;
+use utils::messenger::messenger_from_config;
+use utils::notifier::Notifier;
+use utils::outbox::{EmailOutbox, OutboxWorker};
+use utils::rate_limit::TicketResendLimits;
+use utils::recovery::CheckoutRecoveryScheduler;
+use utils::reminders::ReminderScheduler;
+use utils::signing::TicketSigner;
+use utils::storage::storage_from_config;
+use utils::wallet::WalletPasses;
 
 pub struct CORS;
 
@@ -40,11 +53,47 @@ async fn rocket() -> _ {
     dotenv().ok();
     let config = Config::from_env();
     let db = init_db(&config).await;
+    ensure_indexes(&db).await.expect("Failed to create database indexes");
+    let storage = storage_from_config(&config);
+    let signer = TicketSigner::from_config(&config).expect("Invalid ticket signing configuration");
+    if !signer.is_enabled() {
+        println!("⚠️ TICKET_SIGNING_KEYS not set; QR codes will contain plain ticket codes");
+    }
+    let wallet = WalletPasses::from_config(&config).expect("Invalid wallet pass configuration");
+    let mailer = mailer_from_config(&config).expect("Invalid mail configuration");
+    let outbox = EmailOutbox::new(db.clone(), mailer, &config);
+    let messenger = messenger_from_config(&config).expect("Invalid SMS/WhatsApp configuration");
+    let notifier = Notifier::new(messenger, &config);
+
+    // Batas upload multipart mengikuti MAX_UPLOAD_MB (+ sedikit ruang untuk field form)
+    let upload_limit = ByteUnit::from(config.max_upload_bytes);
+    let limits = Limits::default()
+        .limit("file", upload_limit)
+        .limit("data-form", upload_limit + ByteUnit::Mebibyte(1));
+    // IP klien hanya diambil dari TRUSTED_IP_HEADER (lihat ClientAddr), bukan X-Real-IP bawaan Rocket
+    let figment = rocket::Config::figment().merge(("limits", limits)).merge(("ip_header", false));
 
-    rocket::build()
-        .manage(config)
-        .manage(db)
+    let mut app = rocket::custom(figment)
+        .manage(storage)
+        .manage(signer)
+        .manage(wallet)
+        .manage(outbox)
+        .manage(notifier)
+        .manage(AttendanceFeed::new())
+        .manage(TicketResendLimits::from_config(&config))
         .attach(CORS)
+        .attach(OutboxWorker)
+        .attach(ReminderScheduler)
+        .attach(CheckoutRecoveryScheduler)
+        .attach(AttendanceStatsWorker)
         .mount("/api", public::routes())
         .mount("/api/admin", admin::routes())
+        .mount("/api/staff", staff::routes());
+
+    if config.storage_backend == "local" {
+        std::fs::create_dir_all(&config.media_dir).expect("Failed to create MEDIA_DIR");
+        app = app.mount("/media", FileServer::from(&config.media_dir));
+    }
+
+    app.manage(config).manage(db)
 }
\ No newline at end of file
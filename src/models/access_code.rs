@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccessCode {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub event_id: ObjectId,
+    pub code: String,
+    pub max_uses: Option<i32>,
+    #[serde(default)]
+    pub used_count: i32,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AccessCode {
+    pub fn normalize(code: &str) -> String {
+        code.trim().to_uppercase()
+    }
+
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.valid_from.is_none_or(|from| now >= from)
+            && self.valid_until.is_none_or(|until| now < until)
+            && self.max_uses.is_none_or(|max| self.used_count < max)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAccessCodePayload {
+    pub code: Option<String>,
+    pub max_uses: Option<i32>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+}
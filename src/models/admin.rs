@@ -9,6 +9,43 @@ pub struct Admin {
     pub id: Option<ObjectId>,
     pub email: String,
     pub password_hash: String,
+    #[serde(default)]
+    pub role: AdminRole,
     #[serde(default = "chrono::Utc::now")]
     pub created_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AdminRole {
+    #[default]
+    Admin,
+    Supervisor,
+    Staff,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateStaffPayload {
+    pub email: String,
+    pub password: String,
+    pub role: AdminRole,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffAccount {
+    pub id: Option<String>,
+    pub email: String,
+    pub role: AdminRole,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Admin> for StaffAccount {
+    fn from(admin: Admin) -> Self {
+        StaffAccount {
+            id: admin.id.map(|id| id.to_hex()),
+            email: admin.email,
+            role: admin.role,
+            created_at: admin.created_at,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+use crate::models::order::OrderStatus;
+
+/// Pengumuman email ke pembeli tiket sebuah event (koleksi `announcements`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Announcement {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub event_id: ObjectId,
+    /// Subject yang dikirim (override admin atau hasil render template)
+    pub subject: String,
+    pub message: String,
+    #[serde(default)]
+    pub tiers: Vec<String>,
+    #[serde(default)]
+    pub order_statuses: Vec<OrderStatus>,
+    pub recipient_count: u32,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAnnouncementPayload {
+    #[serde(default)]
+    pub subject: Option<String>,
+    pub message: String,
+    /// Kosong berarti semua tier
+    #[serde(default)]
+    pub tiers: Vec<String>,
+    /// Kosong berarti order `paid` dan `sent`
+    #[serde(default)]
+    pub order_statuses: Vec<OrderStatus>,
+}
+
+/// Jumlah email pengumuman per status outbox.
+#[derive(Debug, Default, Serialize)]
+pub struct AnnouncementProgress {
+    pub pending: u32,
+    pub sending: u32,
+    pub sent: u32,
+    pub dead: u32,
+    pub suppressed: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnnouncementWithProgress {
+    #[serde(flatten)]
+    pub announcement: Announcement,
+    pub progress: AnnouncementProgress,
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+fn default_accepted() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckIn {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub ticket_id: ObjectId,
+    pub event_id: ObjectId,
+    pub code: String,
+    pub gate: String,
+    pub staff_id: String,
+    #[serde(with = "crate::utils::time::millis")]
+    pub scanned_at: DateTime<Utc>,
+    /// `false` untuk percobaan masuk ganda yang ditolak
+    #[serde(default = "default_accepted")]
+    pub accepted: bool,
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Waktu scan persis seperti dikirim device (sebelum dibatasi ke waktu server); kunci dedupe batch
+    #[serde(default, with = "crate::utils::time::option_millis")]
+    pub device_scanned_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub direction: ScanDirection,
+    #[serde(default, with = "crate::utils::time::option_millis")]
+    pub undone_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub undone_by: Option<String>,
+    #[serde(default)]
+    pub undo_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanDirection {
+    #[default]
+    In,
+    Out,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckInPayload {
+    pub event_id: String,
+    /// Kode tiket atau token bertanda tangan hasil scan QR
+    pub scan: String,
+    pub gate: String,
+    #[serde(default)]
+    pub direction: ScanDirection,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScanBatchPayload {
+    pub device_id: String,
+    pub scans: Vec<OfflineScan>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OfflineScan {
+    pub scan: String,
+    pub scanned_at: DateTime<Utc>,
+    pub gate: String,
+    #[serde(default)]
+    pub direction: ScanDirection,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManifestTicket {
+    #[serde(rename = "id")]
+    pub ticket_id: String,
+    #[serde(rename = "c")]
+    pub code: String,
+    #[serde(rename = "t")]
+    pub tier: String,
+    #[serde(rename = "s")]
+    pub status: String,
+    #[serde(rename = "i")]
+    pub inside: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UndoCheckInPayload {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GateCount {
+    pub gate: String,
+    pub checked_in: i64,
+    pub exits: i64,
+    pub duplicates: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TierAttendance {
+    pub tier: String,
+    pub tickets: i64,
+    pub checked_in: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttendanceStats {
+    pub event_id: String,
+    pub total_tickets: i64,
+    pub checked_in: i64,
+    pub no_show: i64,
+    pub void: i64,
+    pub duplicates: i64,
+    pub occupancy: i64,
+    pub venue_capacity: Option<i32>,
+    pub by_gate: Vec<GateCount>,
+    pub by_tier: Vec<TierAttendance>,
+    pub last_scan_at: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArrivalBucket {
+    pub starts_at: DateTime<Utc>,
+    pub gate: String,
+    pub count: i64,
+}
+
+/// Notifikasi ke dashboard live setiap ada hasil scan / pembatalan check-in.
+#[derive(Debug, Clone, Serialize)]
+pub struct AttendanceUpdate {
+    pub event_id: String,
+    pub ticket_id: String,
+    pub gate: String,
+    pub result: String,
+    pub at: DateTime<Utc>,
+}
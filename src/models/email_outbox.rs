@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutboxStatus {
+    /// Menunggu dikirim (termasuk yang dijadwalkan ulang setelah gagal)
+    Pending,
+    Sending,
+    Sent,
+    /// Gagal terus sampai batas percobaan; hanya dikirim lagi lewat retry admin
+    Dead,
+    /// Tidak dikirim karena alamat tujuan ada di daftar suppression
+    Suppressed,
+}
+
+impl std::fmt::Display for OutboxStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutboxStatus::Pending => write!(f, "pending"),
+            OutboxStatus::Sending => write!(f, "sending"),
+            OutboxStatus::Sent => write!(f, "sent"),
+            OutboxStatus::Dead => write!(f, "dead"),
+            OutboxStatus::Suppressed => write!(f, "suppressed"),
+        }
+    }
+}
+
+/// Status pengiriman dari webhook provider setelah email diterima provider.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Delivered,
+    DeliveryDelayed,
+    Bounced,
+    Complained,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxAttachment {
+    pub filename: String,
+    pub content_type: String,
+    #[serde(with = "serde_bytes")]
+    pub content: Vec<u8>,
+}
+
+/// Email yang antre di koleksi `email_outbox`. Waktu disimpan sebagai epoch milidetik
+/// supaya worker bisa memilih email yang jatuh tempo langsung di query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEmail {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    /// Jenis email, mis. `ticket_delivery`
+    pub kind: String,
+    #[serde(default)]
+    pub order_id: Option<ObjectId>,
+    #[serde(default)]
+    pub event_id: Option<ObjectId>,
+    #[serde(default)]
+    pub announcement_id: Option<ObjectId>,
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    #[serde(default)]
+    pub html: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<OutboxAttachment>,
+    pub status: OutboxStatus,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub next_attempt_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub provider_message_id: Option<String>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub sent_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub delivery_status: Option<DeliveryStatus>,
+    #[serde(default)]
+    pub bounce_reason: Option<String>,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub delivery_updated_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub opened_at: Option<DateTime<Utc>>,
+}
+
+/// Ringkasan untuk daftar admin, tanpa isi lampiran.
+#[derive(Debug, Serialize)]
+pub struct OutboxEmailSummary {
+    pub id: String,
+    pub kind: String,
+    pub order_id: Option<String>,
+    pub event_id: Option<String>,
+    pub announcement_id: Option<String>,
+    pub to: String,
+    pub subject: String,
+    pub attachments: Vec<String>,
+    pub status: OutboxStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub provider_message_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub delivery_status: Option<DeliveryStatus>,
+    pub bounce_reason: Option<String>,
+    pub delivery_updated_at: Option<DateTime<Utc>>,
+    pub opened_at: Option<DateTime<Utc>>,
+}
+
+impl From<OutboxEmail> for OutboxEmailSummary {
+    fn from(email: OutboxEmail) -> Self {
+        OutboxEmailSummary {
+            id: email.id.map(|id| id.to_hex()).unwrap_or_default(),
+            kind: email.kind,
+            order_id: email.order_id.map(|id| id.to_hex()),
+            event_id: email.event_id.map(|id| id.to_hex()),
+            announcement_id: email.announcement_id.map(|id| id.to_hex()),
+            to: email.to,
+            subject: email.subject,
+            attachments: email.attachments.into_iter().map(|a| a.filename).collect(),
+            status: email.status,
+            attempts: email.attempts,
+            next_attempt_at: email.next_attempt_at,
+            last_error: email.last_error,
+            provider_message_id: email.provider_message_id,
+            created_at: email.created_at,
+            sent_at: email.sent_at,
+            delivery_status: email.delivery_status,
+            bounce_reason: email.bounce_reason,
+            delivery_updated_at: email.delivery_updated_at,
+            opened_at: email.opened_at,
+        }
+    }
+}
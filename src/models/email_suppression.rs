@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionReason {
+    HardBounce,
+    Complaint,
+}
+
+/// Alamat yang tidak boleh dikirimi email lagi (koleksi `email_suppressions`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailSuppression {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    /// Disimpan dalam huruf kecil
+    pub email: String,
+    pub reason: SuppressionReason,
+    #[serde(default)]
+    pub detail: Option<String>,
+    #[serde(default)]
+    pub provider_message_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
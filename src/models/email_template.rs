@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTemplateKind {
+    OrderConfirmation,
+    TicketDelivery,
+    PaymentReminder,
+    Refund,
+    Cancellation,
+    Announcement,
+    EventReminder,
+}
+
+impl EmailTemplateKind {
+    pub const ALL: [EmailTemplateKind; 7] = [
+        EmailTemplateKind::OrderConfirmation,
+        EmailTemplateKind::TicketDelivery,
+        EmailTemplateKind::PaymentReminder,
+        EmailTemplateKind::Refund,
+        EmailTemplateKind::Cancellation,
+        EmailTemplateKind::Announcement,
+        EmailTemplateKind::EventReminder,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailTemplateKind::OrderConfirmation => "order_confirmation",
+            EmailTemplateKind::TicketDelivery => "ticket_delivery",
+            EmailTemplateKind::PaymentReminder => "payment_reminder",
+            EmailTemplateKind::Refund => "refund",
+            EmailTemplateKind::Cancellation => "cancellation",
+            EmailTemplateKind::Announcement => "announcement",
+            EmailTemplateKind::EventReminder => "event_reminder",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == kind)
+    }
+}
+
+/// Template email tersimpan; `event_id` kosong berarti template global,
+/// selain itu override untuk satu event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTemplate {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub kind: EmailTemplateKind,
+    #[serde(default)]
+    pub event_id: Option<ObjectId>,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateSource {
+    Default,
+    Global,
+    Event,
+}
+
+/// Template yang berlaku untuk satu jenis email beserta asalnya.
+#[derive(Debug, Serialize)]
+pub struct EffectiveEmailTemplate {
+    pub kind: EmailTemplateKind,
+    pub source: TemplateSource,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveEmailTemplatePayload {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Tanpa `order_id` preview memakai data contoh; subject/html/text yang diisi
+/// menggantikan template tersimpan sehingga draft bisa dicoba sebelum disimpan.
+#[derive(Debug, Deserialize)]
+pub struct PreviewEmailTemplatePayload {
+    #[serde(default)]
+    pub order_id: Option<String>,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub html: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
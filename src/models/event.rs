@@ -1,16 +1,295 @@
 use serde::{Deserialize, Serialize};
-use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::bson::{doc, oid::ObjectId, Document};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 
-#[derive(Debug, Serialize, Deserialize)]
+pub const DEFAULT_TIMEZONE: &str = "Asia/Jakarta";
+pub const DEFAULT_TIER: &str = "regular";
+
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub name: String,
+    #[serde(default)]
+    pub slug: String,
     pub description: String,
+    #[serde(with = "crate::utils::time::millis")]
     pub date: DateTime<Utc>,
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
     pub location: String,
     pub price: f64,
     pub total_tickets: i32,
     pub available_tickets: i32,
-}
\ No newline at end of file
+    #[serde(default)]
+    pub tiers: Vec<TicketTier>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub poster: Option<EventImage>,
+    #[serde(default)]
+    pub gallery: Vec<EventImage>,
+    #[serde(default)]
+    pub visibility: EventVisibility,
+    #[serde(default)]
+    pub public_sale_starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub status: EventStatus,
+    #[serde(default)]
+    pub ticket_layout: TicketLayout,
+    #[serde(default)]
+    pub entry_rules: EntryRules,
+    #[serde(default)]
+    pub ticket_delivery: TicketDelivery,
+    /// Diisi saat event dibuat dan setiap kali admin mengubahnya; kosong untuk event lama
+    #[serde(default, with = "crate::utils::time::option_millis")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// `auto`: email tiket dikirim begitu pembayaran lunas; `manual`: admin yang mengirim.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketDelivery {
+    #[default]
+    Auto,
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryRules {
+    #[serde(default)]
+    pub reentry: ReentryPolicy,
+    #[serde(default)]
+    pub venue_capacity: Option<i32>,
+    #[serde(default = "default_capacity_alert_percent")]
+    pub capacity_alert_percent: u8,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum ReentryPolicy {
+    #[default]
+    Single,
+    Unlimited,
+    Limited { max_entries: u32 },
+}
+
+fn default_capacity_alert_percent() -> u8 {
+    90
+}
+
+impl Default for EntryRules {
+    fn default() -> Self {
+        EntryRules {
+            reentry: ReentryPolicy::default(),
+            venue_capacity: None,
+            capacity_alert_percent: default_capacity_alert_percent(),
+        }
+    }
+}
+
+impl EntryRules {
+    /// Jumlah pengunjung di dalam venue yang memicu peringatan kapasitas.
+    pub fn capacity_alert_at(&self) -> Option<i64> {
+        self.venue_capacity
+            .map(|capacity| (capacity as i64 * self.capacity_alert_percent.min(100) as i64 + 99) / 100)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketLayout {
+    #[serde(default)]
+    pub paper: TicketPaper,
+    #[serde(default = "default_accent_color")]
+    pub accent_color: String,
+    #[serde(default)]
+    pub show_description: bool,
+    #[serde(default)]
+    pub footer_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketPaper {
+    #[default]
+    A4,
+    A6,
+}
+
+fn default_accent_color() -> String {
+    "#1E3A8A".to_string()
+}
+
+impl Default for TicketLayout {
+    fn default() -> Self {
+        TicketLayout {
+            paper: TicketPaper::default(),
+            accent_color: default_accent_color(),
+            show_description: false,
+            footer_text: None,
+        }
+    }
+}
+
+impl TicketLayout {
+    /// Warna aksen dalam format `#RRGGBB`, dikembalikan sebagai komponen 0..1.
+    pub fn accent_rgb(&self) -> Option<(f32, f32, f32)> {
+        let hex = self.accent_color.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok().map(|v| v as f32 / 255.0);
+        Some((channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventStatus {
+    Draft,
+    #[default]
+    Published,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketTier {
+    pub name: String,
+    pub price: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventVisibility {
+    #[default]
+    Public,
+    Unlisted,
+    Private,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventImage {
+    pub id: String,
+    pub url: String,
+    pub thumbnail_url: String,
+    pub content_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub key: String,
+    pub thumbnail_key: String,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+impl Event {
+    /// Filter untuk event yang boleh tampil di listing publik.
+    pub fn listed_filter() -> Document {
+        doc! {
+            "visibility": {"$nin": ["unlisted", "private"]},
+            "status": {"$ne": "draft"},
+        }
+    }
+
+    // Event tanpa tier memakai satu tier "regular" dengan harga `price`
+    pub fn default_tier(&self) -> &str {
+        self.tiers.first().map_or(DEFAULT_TIER, |tier| tier.name.as_str())
+    }
+
+    pub fn tier_price(&self, tier: &str) -> Option<f64> {
+        if self.tiers.is_empty() {
+            return (tier == DEFAULT_TIER).then_some(self.price);
+        }
+        self.tiers.iter().find(|t| t.name == tier).map(|t| t.price)
+    }
+
+    /// URL publik event, memakai slug jika ada.
+    pub fn public_url(&self, base_url: &str) -> String {
+        let key = if self.slug.is_empty() {
+            self.id.map(|id| id.to_hex()).unwrap_or_default()
+        } else {
+            self.slug.clone()
+        };
+        format!("{}/api/events/{}", base_url, key)
+    }
+
+    /// Waktu perubahan terakhir; event lama tanpa `updated_at` memakai waktu pembuatan ObjectId.
+    pub fn last_updated(&self) -> DateTime<Utc> {
+        self.updated_at
+            .or_else(|| self.id.map(|id| id.timestamp().to_chrono()))
+            .unwrap_or(self.date)
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(chrono_tz::Asia::Jakarta)
+    }
+
+    pub fn local_date(&self) -> DateTime<Tz> {
+        self.date.with_timezone(&self.tz())
+    }
+
+    /// Salin event ke draft baru pada tanggal lain; jadwal presale ikut bergeser.
+    pub fn duplicate(&self, date: DateTime<Utc>, name: Option<String>) -> Event {
+        let shift = date - self.date;
+        Event {
+            id: None,
+            name: name.unwrap_or_else(|| self.name.clone()),
+            slug: String::new(),
+            date,
+            available_tickets: self.total_tickets,
+            public_sale_starts_at: self.public_sale_starts_at.map(|starts_at| starts_at + shift),
+            status: EventStatus::Draft,
+            ..self.clone()
+        }
+    }
+
+    // Kategori & tag disimpan lowercase supaya filter tidak case-sensitive
+    pub fn normalize_taxonomy(&mut self) {
+        self.category = self
+            .category
+            .as_deref()
+            .map(|c| c.trim().to_lowercase())
+            .filter(|c| !c.is_empty());
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags.iter().map(|t| t.trim().to_lowercase()) {
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        self.tags = tags;
+    }
+
+    // Event private & masa presale hanya bisa dibeli dengan access code
+    pub fn requires_access_code(&self, now: DateTime<Utc>) -> bool {
+        self.visibility == EventVisibility::Private
+            || self.public_sale_starts_at.is_some_and(|starts_at| now < starts_at)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloneEventPayload {
+    pub date: DateTime<Utc>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateEventAccessPayload {
+    pub visibility: EventVisibility,
+    pub public_sale_starts_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTicketDeliveryPayload {
+    pub ticket_delivery: TicketDelivery,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: i64,
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+use crate::models::event::{Event, EntryRules, EventImage, EventStatus, EventVisibility, TicketDelivery, TicketLayout, TicketTier, DEFAULT_TIMEZONE};
+
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventTemplate {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub template_name: String,
+    pub name: String,
+    pub description: String,
+    pub location: String,
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    pub price: f64,
+    pub total_tickets: i32,
+    #[serde(default)]
+    pub tiers: Vec<TicketTier>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub poster: Option<EventImage>,
+    #[serde(default)]
+    pub gallery: Vec<EventImage>,
+    #[serde(default)]
+    pub visibility: EventVisibility,
+    #[serde(default)]
+    pub ticket_layout: TicketLayout,
+    #[serde(default)]
+    pub entry_rules: EntryRules,
+    #[serde(default)]
+    pub ticket_delivery: TicketDelivery,
+    #[serde(default = "chrono::Utc::now")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl EventTemplate {
+    pub fn from_event(template_name: String, event: &Event) -> Self {
+        EventTemplate {
+            id: None,
+            template_name,
+            name: event.name.clone(),
+            description: event.description.clone(),
+            location: event.location.clone(),
+            timezone: event.timezone.clone(),
+            price: event.price,
+            total_tickets: event.total_tickets,
+            tiers: event.tiers.clone(),
+            category: event.category.clone(),
+            tags: event.tags.clone(),
+            poster: event.poster.clone(),
+            gallery: event.gallery.clone(),
+            visibility: event.visibility,
+            ticket_layout: event.ticket_layout.clone(),
+            entry_rules: event.entry_rules.clone(),
+            ticket_delivery: event.ticket_delivery,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn to_event(&self, date: DateTime<Utc>, name: Option<String>) -> Event {
+        Event {
+            id: None,
+            name: name.unwrap_or_else(|| self.name.clone()),
+            slug: String::new(),
+            description: self.description.clone(),
+            date,
+            timezone: self.timezone.clone(),
+            location: self.location.clone(),
+            price: self.price,
+            total_tickets: self.total_tickets,
+            available_tickets: self.total_tickets,
+            tiers: self.tiers.clone(),
+            category: self.category.clone(),
+            tags: self.tags.clone(),
+            poster: self.poster.clone(),
+            gallery: self.gallery.clone(),
+            visibility: self.visibility,
+            public_sale_starts_at: None,
+            status: EventStatus::Draft,
+            ticket_layout: self.ticket_layout.clone(),
+            entry_rules: self.entry_rules.clone(),
+            ticket_delivery: self.ticket_delivery,
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveEventTemplatePayload {
+    pub template_name: String,
+}
@@ -1,3 +1,12 @@
+pub mod access_code;
 pub mod admin;
+pub mod announcement;
+pub mod checkin;
+pub mod email_outbox;
+pub mod email_suppression;
+pub mod email_template;
 pub mod event;
-pub mod order;
\ No newline at end of file
+pub mod event_template;
+pub mod order;
+pub mod reminder;
+pub mod ticket;
\ No newline at end of file
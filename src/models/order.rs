@@ -2,7 +2,17 @@ use serde::{Deserialize, Serialize};
 use mongodb::bson::{doc, oid::ObjectId};
 use chrono::{DateTime, Utc};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::models::event::DEFAULT_TIER;
+
+fn default_tier() -> String {
+    DEFAULT_TIER.to_string()
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
@@ -10,18 +20,64 @@ pub struct Order {
     pub buyer_name: String,
     pub buyer_email: String,
     pub buyer_phone: String,
+    #[serde(default = "default_tier")]
+    pub tier: String,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
     pub status: OrderStatus,
     pub midtrans_order_id: String,
+    #[serde(with = "crate::utils::time::millis")]
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub access_code: Option<String>,
+    /// Email pembeli bounce (dari webhook Resend)
+    #[serde(default)]
+    pub email_bounced: bool,
+    /// Link pembayaran Midtrans, dipakai ulang di email pemulihan checkout
+    #[serde(default)]
+    pub payment_url: Option<String>,
+    #[serde(default)]
+    pub recovery_status: Option<RecoveryStatus>,
+    #[serde(default, with = "crate::utils::time::option_millis")]
+    pub recovery_email_sent_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "crate::utils::time::option_millis")]
+    pub recovered_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub notification_channel: NotificationChannel,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// Preferensi notifikasi pembeli. Email selalu dikirim; `sms`/`whatsapp` menambah
+/// pesan singkat ke `buyer_phone` untuk order, tiket, dan pengingat event.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationChannel {
+    #[default]
+    Email,
+    Sms,
+    Whatsapp,
+}
+
+/// Status pemulihan checkout untuk order yang lama `pending`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoveryStatus {
+    /// Email pengingat pembayaran sudah diantrekan
+    Sent,
+    /// Tidak dikirim karena pembeli sudah punya order lunas untuk event yang sama
+    Skipped,
+    /// Order dibayar setelah email pengingat dikirim
+    Converted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderStatus {
     Pending,
     Paid,
     Sent,
     Failed,
+    /// Sudah dibayar lalu di-refund penuh; tiketnya di-void
+    Refunded,
 }
 
 impl std::fmt::Display for OrderStatus {
@@ -31,14 +87,42 @@ impl std::fmt::Display for OrderStatus {
             OrderStatus::Paid => write!(f, "paid"),
             OrderStatus::Sent => write!(f, "sent"),
             OrderStatus::Failed => write!(f, "failed"),
+            OrderStatus::Refunded => write!(f, "refunded"),
         }
     }
 }
 
+/// Ringkasan pemulihan checkout untuk laporan admin.
+#[derive(Debug, Default, Serialize)]
+pub struct CheckoutRecoveryReport {
+    pub emails_sent: u32,
+    pub converted: u32,
+    pub skipped: u32,
+    /// `converted / emails_sent` dalam persen
+    pub conversion_rate: f64,
+    pub recovered_tickets: u32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateOrderPayload {
     pub event_id: String,
     pub buyer_name: String,
     pub buyer_email: String,
     pub buyer_phone: String,
+    #[serde(default)]
+    pub tier: Option<String>,
+    #[serde(default)]
+    pub quantity: Option<i32>,
+    #[serde(default)]
+    pub access_code: Option<String>,
+    #[serde(default)]
+    pub notification_channel: NotificationChannel,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResendTicketsPayload {
+    pub email: String,
+    /// Nomor order (`ORDER-...`) untuk membatasi ke satu order
+    #[serde(default)]
+    pub order_number: Option<String>,
 }
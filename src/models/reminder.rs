@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+/// Catatan pengingat yang sudah diproses (koleksi `reminder_log`). `_id` berisi
+/// `<order_id>:<offset>` sehingga satu pengingat hanya bisa tercatat sekali per order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderLog {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub order_id: ObjectId,
+    pub event_id: ObjectId,
+    /// Jarak pengingat sebelum event, mis. `7d` atau `12h`
+    pub offset: String,
+    #[serde(default)]
+    pub outbox_id: Option<ObjectId>,
+    pub created_at: DateTime<Utc>,
+}
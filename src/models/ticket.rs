@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use mongodb::bson::oid::ObjectId;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ticket {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub order_id: ObjectId,
+    pub event_id: ObjectId,
+    /// Urutan tiket dalam order; unik bersama `order_id` supaya tiket tidak terbit dua kali
+    #[serde(default)]
+    pub seq: u32,
+    pub code: String,
+    #[serde(default)]
+    pub access_token: String,
+    pub attendee_name: String,
+    pub tier: String,
+    pub status: TicketStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "crate::utils::time::option_millis")]
+    pub checked_in_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub checked_in_gate: Option<String>,
+    #[serde(default)]
+    pub checked_in_by: Option<String>,
+    /// Pengunjung sedang berada di dalam venue (scan masuk tanpa scan keluar)
+    #[serde(default)]
+    pub inside: bool,
+    #[serde(default)]
+    pub entry_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketStatus {
+    Valid,
+    Used,
+    Void,
+}
+
+impl std::fmt::Display for TicketStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TicketStatus::Valid => write!(f, "valid"),
+            TicketStatus::Used => write!(f, "used"),
+            TicketStatus::Void => write!(f, "void"),
+        }
+    }
+}
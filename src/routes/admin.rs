@@ -2,13 +2,33 @@ use rocket::serde::Deserialize;
 use serde_json::json;
 use futures::TryStreamExt;
 
-use rocket::{get, post, routes, State, http::Status, response::status::Custom};
+use rocket::{get, post, put, delete, routes, State, http::Status, response::status::Custom};
+use rocket::form::{Form, FromForm};
+use rocket::fs::TempFile;
 use rocket::serde::json::Json;
-use mongodb::{bson::{doc, oid::ObjectId}, Database, Collection, options::FindOptions};
+use rocket::tokio::io::AsyncReadExt;
+use mongodb::{bson::{self, doc, oid::ObjectId, Document}, Database, Collection, options::{FindOptions, FindOneAndUpdateOptions, ReplaceOptions, ReturnDocument}};
+use uuid::Uuid;
+use chrono_tz::Tz;
 
-use crate::models::{admin::Admin, event::Event, order::{Order, OrderStatus}};
-use crate::utils::{auth::{AdminAuth, verify_password, create_jwt}};
+use crate::models::{admin::{Admin, CreateStaffPayload, StaffAccount}, order::{CheckoutRecoveryReport, Order, OrderStatus}, ticket::Ticket};
+use crate::models::access_code::{AccessCode, CreateAccessCodePayload};
+use crate::models::announcement::{AnnouncementWithProgress, CreateAnnouncementPayload};
+use crate::models::email_outbox::OutboxEmailSummary;
+use crate::models::email_suppression::EmailSuppression;
+use crate::models::event::{Event, EventImage, CloneEventPayload, EntryRules, ReentryPolicy, TicketLayout, UpdateEventAccessPayload, UpdateTicketDeliveryPayload};
+use crate::models::email_template::{EffectiveEmailTemplate, EmailTemplate, EmailTemplateKind, PreviewEmailTemplatePayload, RenderedEmail, SaveEmailTemplatePayload};
+use crate::models::event_template::{EventTemplate, SaveEventTemplatePayload};
+use crate::utils::{auth::{AdminAuth, hash_password, verify_password, create_jwt}, slug::unique_event_slug};
+use crate::utils::{media::process_image, storage::Storage, tickets::find_tickets};
+use crate::utils::suppressions::{find_suppressions, remove_suppression};
+use crate::utils::recovery::recovery_report;
+use crate::utils::announcements::{announcement_progress, find_announcements, send_announcement};
+use crate::utils::email_templates::{copy_event_templates, email_context, find_effective_template, render_email, sample_context};
+use crate::utils::{delivery::{deliver_order_tickets, AUTO_DELIVERY_MESSAGE}, notifier::Notifier, outbox::EmailOutbox, signing::TicketSigner, wallet::WalletPasses};
 use crate::config::Config;
+use crate::db::is_duplicate_key;
+use crate::utils::time::stored_time;
 
 #[derive(Deserialize)]
 pub struct LoginPayload {
@@ -41,9 +61,9 @@ pub async fn login(
 
     if let Some(admin_doc) = admin {
         if verify_password(&payload.password, &admin_doc.password_hash) {
-            let token = create_jwt(&admin_doc.email, &config.jwt_secret)
+            let token = create_jwt(&admin_doc.email, admin_doc.role, &config.jwt_secret)
                 .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Token generation failed"}))))?;
-            Ok(Json(json!({"token": token})))
+            Ok(Json(json!({"token": token, "role": admin_doc.role})))
         } else {
             Err(Custom(Status::Unauthorized, Json(json!({"error": "Invalid credentials"}))))
         }
@@ -52,6 +72,70 @@ pub async fn login(
     }
 }
 
+#[get("/staff")]
+pub async fn get_staff(
+    db: &State<Database>,
+    _admin: AdminAuth
+) -> Result<Json<Vec<StaffAccount>>, Status> {
+    let collection: Collection<Admin> = db.collection("admins");
+    let find_options = FindOptions::builder()
+        .sort(doc! { "email": 1 })
+        .build();
+
+    let mut cursor = collection
+        .find(doc! {}, find_options)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut accounts = Vec::new();
+    while let Some(account) = cursor
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)? {
+        accounts.push(StaffAccount::from(account));
+    }
+    Ok(Json(accounts))
+}
+
+#[post("/staff", data = "<payload>")]
+pub async fn create_staff(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    payload: Json<CreateStaffPayload>
+) -> Result<Json<StaffAccount>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Admin> = db.collection("admins");
+    let email = payload.email.trim().to_lowercase();
+    if email.is_empty() || payload.password.len() < 8 {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "Email and a password of at least 8 characters are required"}))));
+    }
+
+    let existing = collection
+        .count_documents(doc! {"email": &email}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+    if existing > 0 {
+        return Err(Custom(Status::Conflict, Json(json!({"error": "Account already exists"}))));
+    }
+
+    let password_hash = hash_password(&payload.password)
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to hash password"}))))?;
+    let mut account = Admin {
+        id: None,
+        email,
+        password_hash,
+        role: payload.role,
+        created_at: chrono::Utc::now(),
+    };
+
+    let result = collection
+        .insert_one(&account, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to create account"}))))?;
+
+    account.id = result.inserted_id.as_object_id();
+    Ok(Json(StaffAccount::from(account)))
+}
+
 #[get("/events")]
 pub async fn admin_get_events(
     db: &State<Database>, 
@@ -81,29 +165,823 @@ pub async fn create_event(
 ) -> Result<Json<Event>, Status> {
     let collection: Collection<Event> = db.collection("events");
     let mut new_event = payload.into_inner();
+    if new_event.timezone.parse::<Tz>().is_err() {
+        return Err(Status::BadRequest);
+    }
     new_event.available_tickets = new_event.total_tickets;
-    
-    let result = collection
-        .insert_one(&new_event, None)
+    new_event.poster = None;
+    new_event.gallery = Vec::new();
+
+    let new_event = insert_new_event(&collection, new_event)
         .await
         .map_err(|_| Status::InternalServerError)?;
-    
-    new_event.id = Some(result.inserted_id.as_object_id().unwrap());
     Ok(Json(new_event))
 }
 
-#[get("/orders")]
+// Setiap perubahan event ikut memperbarui `updated_at` (dipakai feed Atom)
+fn touch_event(mut update: Document) -> Document {
+    let now = stored_time(chrono::Utc::now());
+    match update.get_document_mut("$set") {
+        Ok(set) => { set.insert("updated_at", now); }
+        Err(_) => { update.insert("$set", doc! {"updated_at": now}); }
+    }
+    update
+}
+
+const SLUG_INSERT_ATTEMPTS: u32 = 5;
+
+async fn insert_new_event(
+    collection: &Collection<Event>,
+    mut event: Event,
+) -> Result<Event, mongodb::error::Error> {
+    event.normalize_taxonomy();
+    event.updated_at = Some(chrono::Utc::now());
+    let slug_source = if event.slug.trim().is_empty() { event.name.clone() } else { event.slug.clone() };
+
+    // Dua event dengan nama sama bisa lolos cek slug bersamaan; unique index yang memutuskan
+    let mut attempts = 0;
+    loop {
+        event.slug = unique_event_slug(collection, &slug_source).await?;
+        match collection.insert_one(&event, None).await {
+            Ok(result) => {
+                event.id = result.inserted_id.as_object_id();
+                return Ok(event);
+            }
+            Err(e) if is_duplicate_key(&e) && attempts < SLUG_INSERT_ATTEMPTS => attempts += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[post("/events/<id>/clone", data = "<payload>")]
+pub async fn clone_event(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<CloneEventPayload>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let source = collection
+        .find_one(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    let payload = payload.into_inner();
+    let draft = source.duplicate(payload.date, payload.name);
+    let draft = insert_new_event(&collection, draft)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to clone event"}))))?;
+    if let Some(draft_id) = draft.id {
+        copy_event_templates(db, object_id, draft_id)
+            .await
+            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to copy email templates"}))))?;
+    }
+
+    Ok(Json(draft))
+}
+
+#[post("/events/<id>/publish")]
+pub async fn publish_event(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let updated = collection
+        .find_one_and_update(doc! {"_id": object_id}, touch_event(doc! {"$set": {"status": "published"}}), options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    Ok(Json(updated))
+}
+
+#[get("/event_templates")]
+pub async fn get_event_templates(
+    db: &State<Database>,
+    _admin: AdminAuth
+) -> Result<Json<Vec<EventTemplate>>, Status> {
+    let collection: Collection<EventTemplate> = db.collection("event_templates");
+    let find_options = FindOptions::builder()
+        .sort(doc! { "template_name": 1 })
+        .build();
+
+    let mut cursor = collection
+        .find(doc! {}, find_options)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut templates = Vec::new();
+    while let Some(template) = cursor
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)? {
+        templates.push(template);
+    }
+    Ok(Json(templates))
+}
+
+#[post("/event_templates", data = "<payload>")]
+pub async fn create_event_template(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    payload: Json<EventTemplate>
+) -> Result<Json<EventTemplate>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<EventTemplate> = db.collection("event_templates");
+    let mut template = payload.into_inner();
+    template.id = None;
+    template.poster = None;
+    template.gallery = Vec::new();
+    template.created_at = chrono::Utc::now();
+
+    if template.template_name.trim().is_empty() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "template_name is required"}))));
+    }
+    if template.timezone.parse::<Tz>().is_err() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "Invalid timezone"}))));
+    }
+
+    let result = collection
+        .insert_one(&template, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to create template"}))))?;
+
+    template.id = result.inserted_id.as_object_id();
+    Ok(Json(template))
+}
+
+#[post("/events/<id>/save_as_template", data = "<payload>")]
+pub async fn save_event_as_template(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<SaveEventTemplatePayload>
+) -> Result<Json<EventTemplate>, Custom<Json<serde_json::Value>>> {
+    let event_collection: Collection<Event> = db.collection("events");
+    let collection: Collection<EventTemplate> = db.collection("event_templates");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    if payload.template_name.trim().is_empty() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "template_name is required"}))));
+    }
+
+    let event = event_collection
+        .find_one(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    let mut template = EventTemplate::from_event(payload.into_inner().template_name, &event);
+    let result = collection
+        .insert_one(&template, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to create template"}))))?;
+
+    template.id = result.inserted_id.as_object_id();
+    Ok(Json(template))
+}
+
+#[post("/event_templates/<id>/events", data = "<payload>")]
+pub async fn create_event_from_template(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<CloneEventPayload>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<EventTemplate> = db.collection("event_templates");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid template ID"}))))?;
+
+    let template = collection
+        .find_one(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Template not found"}))))?;
+
+    let payload = payload.into_inner();
+    let draft = template.to_event(payload.date, payload.name);
+    let draft = insert_new_event(&db.collection("events"), draft)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to create event"}))))?;
+
+    Ok(Json(draft))
+}
+
+#[delete("/event_templates/<id>")]
+pub async fn delete_event_template(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<EventTemplate> = db.collection("event_templates");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid template ID"}))))?;
+
+    let result = collection
+        .delete_one(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+
+    if result.deleted_count == 0 {
+        return Err(Custom(Status::NotFound, Json(json!({"error": "Template not found"}))));
+    }
+    Ok(Json(json!({"message": "Template deleted"})))
+}
+
+fn parse_template_kind(kind: &str) -> Result<EmailTemplateKind, Custom<Json<serde_json::Value>>> {
+    EmailTemplateKind::parse(kind)
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Unknown email template"}))))
+}
+
+async fn find_template_event(db: &Database, event_id: Option<&str>) -> Result<Option<Event>, Custom<Json<serde_json::Value>>> {
+    let Some(event_id) = event_id else {
+        return Ok(None);
+    };
+    let object_id = ObjectId::parse_str(event_id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+    db.collection::<Event>("events")
+        .find_one(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))
+        .map(Some)
+}
+
+/// Template yang berlaku untuk setiap jenis email; dengan `event_id` termasuk override event tersebut.
+#[get("/email_templates?<event_id>")]
+pub async fn get_email_templates(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    event_id: Option<&str>
+) -> Result<Json<Vec<EffectiveEmailTemplate>>, Custom<Json<serde_json::Value>>> {
+    let event = find_template_event(db, event_id).await?;
+
+    let mut templates = Vec::new();
+    for kind in EmailTemplateKind::ALL {
+        let template = find_effective_template(db, kind, event.as_ref().and_then(|e| e.id))
+            .await
+            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+        templates.push(template);
+    }
+    Ok(Json(templates))
+}
+
+/// Simpan template global, atau override event jika `event_id` diisi. Template harus
+/// bisa dirender dengan data contoh sebelum disimpan.
+#[put("/email_templates/<kind>?<event_id>", data = "<payload>")]
+pub async fn save_email_template(
+    db: &State<Database>,
+    config: &State<Config>,
+    _admin: AdminAuth,
+    kind: &str,
+    event_id: Option<&str>,
+    payload: Json<SaveEmailTemplatePayload>
+) -> Result<Json<EffectiveEmailTemplate>, Custom<Json<serde_json::Value>>> {
+    let kind = parse_template_kind(kind)?;
+    let event = find_template_event(db, event_id).await?;
+
+    let payload = payload.into_inner();
+    let rendered = render_email(&payload.subject, &payload.html, &payload.text, &sample_context(config, event.as_ref()))
+        .map_err(|e| Custom(Status::UnprocessableEntity, Json(json!({
+            "error": "Template failed to render",
+            "details": e.to_string()
+        }))))?;
+    if rendered.subject.is_empty() {
+        return Err(Custom(Status::UnprocessableEntity, Json(json!({"error": "Subject must not be empty"}))));
+    }
+
+    let event_id = event.and_then(|e| e.id);
+    let template = EmailTemplate {
+        id: None,
+        kind,
+        event_id,
+        subject: payload.subject,
+        html: payload.html,
+        text: payload.text,
+        updated_at: chrono::Utc::now(),
+    };
+    let options = ReplaceOptions::builder().upsert(true).build();
+    db.collection::<EmailTemplate>("email_templates")
+        .replace_one(doc! {"kind": kind.as_str(), "event_id": event_id}, &template, options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to save template"}))))?;
+
+    let saved = find_effective_template(db, kind, event_id)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+    Ok(Json(saved))
+}
+
+/// Hapus template tersimpan sehingga kembali ke template global / bawaan.
+#[delete("/email_templates/<kind>?<event_id>")]
+pub async fn delete_email_template(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    kind: &str,
+    event_id: Option<&str>
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let kind = parse_template_kind(kind)?;
+    let event_id = find_template_event(db, event_id).await?.and_then(|e| e.id);
+
+    let result = db
+        .collection::<EmailTemplate>("email_templates")
+        .delete_one(doc! {"kind": kind.as_str(), "event_id": event_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+
+    if result.deleted_count == 0 {
+        return Err(Custom(Status::NotFound, Json(json!({"error": "Template not found"}))));
+    }
+    Ok(Json(json!({"message": "Template deleted"})))
+}
+
+/// Render template dengan data order sungguhan (`order_id`) atau data contoh.
+#[post("/email_templates/<kind>/preview?<event_id>", data = "<payload>")]
+pub async fn preview_email_template(
+    db: &State<Database>,
+    config: &State<Config>,
+    wallet: &State<WalletPasses>,
+    _admin: AdminAuth,
+    kind: &str,
+    event_id: Option<&str>,
+    payload: Json<PreviewEmailTemplatePayload>
+) -> Result<Json<RenderedEmail>, Custom<Json<serde_json::Value>>> {
+    let kind = parse_template_kind(kind)?;
+    let mut event = find_template_event(db, event_id).await?;
+    let payload = payload.into_inner();
+
+    let order = match &payload.order_id {
+        Some(order_id) => {
+            let object_id = ObjectId::parse_str(order_id)
+                .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid order ID"}))))?;
+            let order = db
+                .collection::<Order>("orders")
+                .find_one(doc! {"_id": object_id}, None)
+                .await
+                .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+                .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Order not found"}))))?;
+            if event.is_none() {
+                event = find_template_event(db, Some(&order.event_id.to_hex())).await?;
+            }
+            Some(order)
+        }
+        None => None,
+    };
+
+    let context = match (&order, &event) {
+        (Some(order), Some(event)) => {
+            let tickets = find_tickets(db, doc! {"order_id": order.id, "status": {"$ne": "void"}})
+                .await
+                .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+            let tickets: Vec<&Ticket> = tickets.iter().collect();
+            let message = payload.message.as_deref().unwrap_or(AUTO_DELIVERY_MESSAGE);
+            email_context(config, wallet, event, order, &tickets, message, None)
+        }
+        _ => sample_context(config, event.as_ref()),
+    };
+
+    let stored = find_effective_template(db, kind, event.as_ref().and_then(|e| e.id))
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+    let rendered = render_email(
+        payload.subject.as_deref().unwrap_or(&stored.subject),
+        payload.html.as_deref().unwrap_or(&stored.html),
+        payload.text.as_deref().unwrap_or(&stored.text),
+        &context,
+    )
+    .map_err(|e| Custom(Status::UnprocessableEntity, Json(json!({
+        "error": "Template failed to render",
+        "details": e.to_string()
+    }))))?;
+    Ok(Json(rendered))
+}
+
+#[derive(FromForm)]
+pub struct ImageUpload<'r> {
+    file: TempFile<'r>,
+}
+
+async fn store_event_image(
+    storage: &dyn Storage,
+    config: &Config,
+    event_id: &ObjectId,
+    file: &TempFile<'_>,
+) -> Result<EventImage, Custom<Json<serde_json::Value>>> {
+    let mut bytes = Vec::new();
+    file.open()
+        .await
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Failed to read uploaded file"}))))?
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Failed to read uploaded file"}))))?;
+
+    let processed = process_image(&bytes, config.max_upload_bytes)
+        .map_err(|e| Custom(Status::UnprocessableEntity, Json(json!({"error": e.to_string()}))))?;
+
+    let image_id = Uuid::new_v4().simple().to_string();
+    let key = format!("events/{}/{}.{}", event_id.to_hex(), image_id, processed.extension);
+    let thumbnail_key = format!("events/{}/{}_thumb.jpg", event_id.to_hex(), image_id);
+
+    let storage_error = |e: anyhow::Error| {
+        Custom(Status::InternalServerError, Json(json!({
+            "error": "Failed to store image",
+            "details": e.to_string()
+        })))
+    };
+    let url = storage.put(&key, &bytes, processed.content_type).await.map_err(storage_error)?;
+    let thumbnail_url = storage.put(&thumbnail_key, &processed.thumbnail, "image/jpeg").await.map_err(storage_error)?;
+
+    Ok(EventImage {
+        id: image_id,
+        url,
+        thumbnail_url,
+        content_type: processed.content_type.to_string(),
+        width: processed.width,
+        height: processed.height,
+        key,
+        thumbnail_key,
+        uploaded_at: chrono::Utc::now(),
+    })
+}
+
+// File gambar bisa dipakai bersama oleh event hasil clone/template,
+// jadi hanya dihapus jika sudah tidak direferensikan lagi.
+async fn delete_event_image(db: &Database, storage: &dyn Storage, image: &EventImage) {
+    let filter = doc! {"$or": [{"poster.key": &image.key}, {"gallery.key": &image.key}]};
+    let events_using = db.collection::<Event>("events").count_documents(filter.clone(), None).await;
+    let templates_using = db.collection::<EventTemplate>("event_templates").count_documents(filter, None).await;
+    match (events_using, templates_using) {
+        (Ok(0), Ok(0)) => {}
+        (Ok(_), Ok(_)) => return,
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Failed to check media references for {}: {:?}", image.key, e);
+            return;
+        }
+    }
+
+    for key in [&image.key, &image.thumbnail_key] {
+        if let Err(e) = storage.delete(key).await {
+            eprintln!("Failed to delete media {}: {:?}", key, e);
+        }
+    }
+}
+
+#[post("/events/<id>/poster", data = "<upload>")]
+pub async fn upload_event_poster(
+    db: &State<Database>,
+    config: &State<Config>,
+    storage: &State<Box<dyn Storage>>,
+    _admin: AdminAuth,
+    id: &str,
+    upload: Form<ImageUpload<'_>>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let event = collection
+        .find_one(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    let image = store_event_image(storage.as_ref(), config, &object_id, &upload.file).await?;
+    let image_bson = bson::to_bson(&image)
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to encode image"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let updated = collection
+        .find_one_and_update(doc! {"_id": object_id}, touch_event(doc! {"$set": {"poster": image_bson}}), options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    if let Some(old_poster) = event.poster {
+        delete_event_image(db, storage.as_ref(), &old_poster).await;
+    }
+
+    Ok(Json(updated))
+}
+
+#[post("/events/<id>/gallery", data = "<upload>")]
+pub async fn upload_event_gallery_image(
+    db: &State<Database>,
+    config: &State<Config>,
+    storage: &State<Box<dyn Storage>>,
+    _admin: AdminAuth,
+    id: &str,
+    upload: Form<ImageUpload<'_>>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let exists = collection
+        .count_documents(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+    if exists == 0 {
+        return Err(Custom(Status::NotFound, Json(json!({"error": "Event not found"}))));
+    }
+
+    let image = store_event_image(storage.as_ref(), config, &object_id, &upload.file).await?;
+    let image_bson = bson::to_bson(&image)
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to encode image"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let updated = collection
+        .find_one_and_update(doc! {"_id": object_id}, touch_event(doc! {"$push": {"gallery": image_bson}}), options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    Ok(Json(updated))
+}
+
+#[delete("/events/<id>/gallery/<image_id>")]
+pub async fn delete_event_gallery_image(
+    db: &State<Database>,
+    storage: &State<Box<dyn Storage>>,
+    _admin: AdminAuth,
+    id: &str,
+    image_id: &str
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::Before).build();
+    let previous = collection
+        .find_one_and_update(
+            doc! {"_id": object_id, "gallery.id": image_id},
+            touch_event(doc! {"$pull": {"gallery": {"id": image_id}}}),
+            options
+        )
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Image not found"}))))?;
+
+    let mut updated = previous;
+    if let Some(position) = updated.gallery.iter().position(|image| image.id == image_id) {
+        let removed = updated.gallery.remove(position);
+        delete_event_image(db, storage.as_ref(), &removed).await;
+    }
+
+    Ok(Json(updated))
+}
+
+#[put("/events/<id>/access", data = "<payload>")]
+pub async fn update_event_access(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<UpdateEventAccessPayload>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let update = doc! {"$set": {
+        "visibility": bson::to_bson(&payload.visibility)
+            .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid visibility"}))))?,
+        "public_sale_starts_at": bson::to_bson(&payload.public_sale_starts_at)
+            .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid public_sale_starts_at"}))))?,
+    }};
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let updated = collection
+        .find_one_and_update(doc! {"_id": object_id}, touch_event(update), options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    Ok(Json(updated))
+}
+
+#[put("/events/<id>/ticket_layout", data = "<payload>")]
+pub async fn update_ticket_layout(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<TicketLayout>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    if payload.accent_rgb().is_none() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "accent_color must be in #RRGGBB format"}))));
+    }
+    let layout = bson::to_bson(&payload.into_inner())
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid ticket layout"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let updated = collection
+        .find_one_and_update(doc! {"_id": object_id}, touch_event(doc! {"$set": {"ticket_layout": layout}}), options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    Ok(Json(updated))
+}
+
+#[put("/events/<id>/entry_rules", data = "<payload>")]
+pub async fn update_entry_rules(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<EntryRules>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    if payload.reentry == (ReentryPolicy::Limited { max_entries: 0 }) {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "max_entries must be at least 1"}))));
+    }
+    if payload.venue_capacity.is_some_and(|capacity| capacity <= 0) {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "venue_capacity must be positive"}))));
+    }
+    if !(1..=100).contains(&payload.capacity_alert_percent) {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "capacity_alert_percent must be between 1 and 100"}))));
+    }
+    let rules = bson::to_bson(&payload.into_inner())
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid entry rules"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let updated = collection
+        .find_one_and_update(doc! {"_id": object_id}, touch_event(doc! {"$set": {"entry_rules": rules}}), options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    Ok(Json(updated))
+}
+
+#[put("/events/<id>/ticket_delivery", data = "<payload>")]
+pub async fn update_ticket_delivery(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<UpdateTicketDeliveryPayload>
+) -> Result<Json<Event>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Event> = db.collection("events");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let delivery = bson::to_bson(&payload.ticket_delivery)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid ticket_delivery"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let updated = collection
+        .find_one_and_update(doc! {"_id": object_id}, touch_event(doc! {"$set": {"ticket_delivery": delivery}}), options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update event"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    Ok(Json(updated))
+}
+
+#[get("/events/<id>/access_codes")]
+pub async fn get_access_codes(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str
+) -> Result<Json<Vec<AccessCode>>, Status> {
+    let collection: Collection<AccessCode> = db.collection("access_codes");
+    let object_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let find_options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .build();
+
+    let mut cursor = collection
+        .find(doc! {"event_id": object_id}, find_options)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut codes = Vec::new();
+    while let Some(code) = cursor
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)? {
+        codes.push(code);
+    }
+    Ok(Json(codes))
+}
+
+#[post("/events/<id>/access_codes", data = "<payload>")]
+pub async fn create_access_code(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    payload: Json<CreateAccessCodePayload>
+) -> Result<Json<AccessCode>, Custom<Json<serde_json::Value>>> {
+    let event_collection: Collection<Event> = db.collection("events");
+    let collection: Collection<AccessCode> = db.collection("access_codes");
+    let event_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+
+    let event_exists = event_collection
+        .count_documents(doc! {"_id": event_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+    if event_exists == 0 {
+        return Err(Custom(Status::NotFound, Json(json!({"error": "Event not found"}))));
+    }
+
+    if payload.max_uses.is_some_and(|max| max <= 0) {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "max_uses must be greater than zero"}))));
+    }
+    if let (Some(from), Some(until)) = (payload.valid_from, payload.valid_until) {
+        if from >= until {
+            return Err(Custom(Status::BadRequest, Json(json!({"error": "valid_from must be before valid_until"}))));
+        }
+    }
+
+    let code = match payload.code.as_deref().map(AccessCode::normalize) {
+        Some(code) if !code.is_empty() => code,
+        _ => Uuid::new_v4().simple().to_string()[..8].to_uppercase(),
+    };
+
+    let duplicate = collection
+        .count_documents(doc! {"event_id": event_id, "code": &code}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+    if duplicate > 0 {
+        return Err(Custom(Status::Conflict, Json(json!({"error": "Access code already exists for this event"}))));
+    }
+
+    let mut access_code = AccessCode {
+        id: None,
+        event_id,
+        code,
+        max_uses: payload.max_uses,
+        used_count: 0,
+        valid_from: payload.valid_from,
+        valid_until: payload.valid_until,
+        created_at: chrono::Utc::now(),
+    };
+
+    let result = collection
+        .insert_one(&access_code, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to create access code"}))))?;
+
+    access_code.id = result.inserted_id.as_object_id();
+    Ok(Json(access_code))
+}
+
+#[delete("/events/<id>/access_codes/<code_id>")]
+pub async fn delete_access_code(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    code_id: &str
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<AccessCode> = db.collection("access_codes");
+    let event_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+    let code_object_id = ObjectId::parse_str(code_id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid access code ID"}))))?;
+
+    let result = collection
+        .delete_one(doc! {"_id": code_object_id, "event_id": event_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+
+    if result.deleted_count == 0 {
+        return Err(Custom(Status::NotFound, Json(json!({"error": "Access code not found"}))));
+    }
+    Ok(Json(json!({"message": "Access code deleted"})))
+}
+
+#[get("/orders?<email_bounced>")]
 pub async fn get_orders(
     db: &State<Database>, 
-    _admin: AdminAuth
+    _admin: AdminAuth,
+    email_bounced: Option<bool>
 ) -> Result<Json<Vec<Order>>, Status> {
     let collection: Collection<Order> = db.collection("orders");
     let find_options = FindOptions::builder()
         .sort(doc! { "created_at": -1 })
         .build();
     
+    let filter = match email_bounced {
+        Some(true) => doc! {"email_bounced": true},
+        Some(false) => doc! {"email_bounced": {"$ne": true}},
+        None => doc! {},
+    };
     let mut cursor = collection
-        .find(doc! {}, find_options)
+        .find(filter, find_options)
         .await
         .map_err(|_| Status::InternalServerError)?;
     
@@ -117,16 +995,77 @@ pub async fn get_orders(
     Ok(Json(orders))
 }
 
+#[get("/orders/<id>/tickets")]
+pub async fn get_order_tickets(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str
+) -> Result<Json<Vec<Ticket>>, Status> {
+    let object_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let tickets = find_tickets(db, doc! {"order_id": object_id})
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    Ok(Json(tickets))
+}
+
+#[get("/events/<id>/tickets?<status>")]
+pub async fn get_event_tickets(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str,
+    status: Option<&str>
+) -> Result<Json<Vec<Ticket>>, Status> {
+    let object_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let mut filter = doc! {"event_id": object_id};
+    if let Some(status) = status {
+        filter.insert("status", status);
+    }
+
+    let tickets = find_tickets(db, filter)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    Ok(Json(tickets))
+}
+
+#[post("/tickets/<id>/void")]
+pub async fn void_ticket(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str
+) -> Result<Json<Ticket>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Ticket> = db.collection("tickets");
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid ticket ID"}))))?;
+
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+    let ticket = collection
+        // Pemegang tiket yang sedang di dalam tidak lagi dihitung dalam okupansi
+        .find_one_and_update(doc! {"_id": object_id}, doc! {"$set": {"status": "void", "inside": false}}, options)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update ticket"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Ticket not found"}))))?;
+
+    Ok(Json(ticket))
+}
+
 #[derive(Deserialize)]
 pub struct SendTicketPayload {
-    subject: String,
-    message: String,
+    #[serde(default)]
+    subject: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
 }
 
+/// Kirim (ulang) email tiket untuk order yang sudah dibayar atau sudah pernah dikirim.
 #[post("/orders/<id>/send_ticket", data = "<payload>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn send_ticket(
     db: &State<Database>, 
     config: &State<Config>, 
+    signer: &State<TicketSigner>,
+    wallet: &State<WalletPasses>,
+    outbox: &State<EmailOutbox>,
+    notifier: &State<Notifier>,
     _admin: AdminAuth, 
     id: &str, 
     payload: Json<SendTicketPayload>
@@ -141,59 +1080,210 @@ pub async fn send_ticket(
         .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
         .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Order not found"}))))?;
 
-    if order.status != OrderStatus::Paid {
+    if order.status != OrderStatus::Paid && order.status != OrderStatus::Sent {
         return Err(Custom(Status::BadRequest, Json(json!({"error": "Order is not paid"}))));
     }
+    let resend = order.status == OrderStatus::Sent;
 
-    let resend_from_email = config.resend_from_email.clone();
-    let resend_api_key = config.resend_api_key.clone();
+    let outbox_id = deliver_order_tickets(db, config, signer, wallet, outbox, notifier, &order, payload.subject.as_deref(), payload.message.as_deref().unwrap_or_default())
+        .await
+        .map_err(|e| Custom(Status::InternalServerError, Json(json!({
+            "error": "Failed to send ticket",
+            "details": e.to_string()
+        }))))?;
+
+    let message = if resend { "Ticket resend queued" } else { "Ticket queued for delivery" };
+    Ok(Json(json!({"message": message, "outbox_id": outbox_id.to_hex()})))
+}
+
+#[get("/email_outbox?<status>&<order_id>")]
+pub async fn get_email_outbox(
+    outbox: &State<EmailOutbox>,
+    _admin: AdminAuth,
+    status: Option<&str>,
+    order_id: Option<&str>
+) -> Result<Json<Vec<OutboxEmailSummary>>, Status> {
+    let mut filter = doc! {};
+    if let Some(status) = status {
+        filter.insert("status", status);
+    }
+    if let Some(order_id) = order_id {
+        filter.insert("order_id", ObjectId::parse_str(order_id).map_err(|_| Status::BadRequest)?);
+    }
+
+    let emails = outbox
+        .list(filter)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    Ok(Json(emails.into_iter().map(OutboxEmailSummary::from).collect()))
+}
 
-    let email_body = format!(
-        "Halo {},\n\n{}\n\nTerima kasih atas pembelian tiket Anda.",
-        order.buyer_name,
-        payload.message
-    );
+/// Kirim ulang email yang sudah dead-letter (atau percepat yang sedang menunggu retry).
+#[post("/email_outbox/<id>/retry")]
+pub async fn retry_outbox_email(
+    outbox: &State<EmailOutbox>,
+    _admin: AdminAuth,
+    id: &str
+) -> Result<Json<OutboxEmailSummary>, Custom<Json<serde_json::Value>>> {
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid email ID"}))))?;
 
-    let resend_payload = json!({
-        "from": resend_from_email,
-        "to": [order.buyer_email.clone()],
-        "subject": payload.subject.clone(),
-        "text": email_body
-    });
+    let email = outbox
+        .retry(object_id)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "No retryable email found"}))))?;
+    Ok(Json(email.into()))
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post("https://api.resend.com/emails")
-        .header("Authorization", format!("Bearer {}", resend_api_key))
-        .header("Content-Type", "application/json")
-        .json(&resend_payload)
-        .send()
+/// Kirim pengumuman ke pembeli event (satu email per alamat), difilter per tier dan status order.
+#[post("/events/<id>/announcements", data = "<payload>")]
+pub async fn create_announcement(
+    db: &State<Database>,
+    config: &State<Config>,
+    wallet: &State<WalletPasses>,
+    outbox: &State<EmailOutbox>,
+    admin: AdminAuth,
+    id: &str,
+    payload: Json<CreateAnnouncementPayload>
+) -> Result<Json<AnnouncementWithProgress>, Custom<Json<serde_json::Value>>> {
+    if payload.message.trim().is_empty() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "Message is required"}))));
+    }
+    let object_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+    let event = db.collection::<Event>("events")
+        .find_one(doc! {"_id": object_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+
+    let announcement = send_announcement(db, config, wallet, outbox, &event, payload.into_inner(), &admin.email)
         .await
         .map_err(|e| Custom(Status::InternalServerError, Json(json!({
-            "error": "Failed to send email",
+            "error": "Failed to send announcement",
             "details": e.to_string()
-        }))))?;
+        }))))?
+        .ok_or_else(|| Custom(Status::BadRequest, Json(json!({"error": "No orders match the announcement filter"}))))?;
 
-    if response.status().is_success() {
-        collection
-            .update_one(
-                doc! {"_id": object_id}, 
-                doc! {"$set": {"status": "sent"}}, 
-                None
-            )
+    let progress = match announcement.id {
+        Some(announcement_id) => announcement_progress(db, announcement_id)
             .await
-            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update order status"}))))?;
+            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?,
+        None => Default::default(),
+    };
+    Ok(Json(AnnouncementWithProgress { announcement, progress }))
+}
 
-        Ok(Json(json!({"message": "Ticket sent successfully"})))
-    } else {
-        let err_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        Err(Custom(Status::InternalServerError, Json(json!({
-            "error": "Resend API error",
-            "details": err_text
-        }))))
+/// Riwayat pengumuman event beserta progres pengirimannya di outbox.
+#[get("/events/<id>/announcements")]
+pub async fn get_announcements(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    id: &str
+) -> Result<Json<Vec<AnnouncementWithProgress>>, Status> {
+    let object_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let announcements = find_announcements(db, object_id)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut result = Vec::with_capacity(announcements.len());
+    for announcement in announcements {
+        let progress = match announcement.id {
+            Some(announcement_id) => announcement_progress(db, announcement_id)
+                .await
+                .map_err(|_| Status::InternalServerError)?,
+            None => Default::default(),
+        };
+        result.push(AnnouncementWithProgress { announcement, progress });
+    }
+    Ok(Json(result))
+}
+
+/// Jumlah email pemulihan checkout yang terkirim dan berapa yang akhirnya dibayar.
+#[get("/reports/checkout_recovery?<event_id>")]
+pub async fn get_checkout_recovery_report(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    event_id: Option<&str>
+) -> Result<Json<CheckoutRecoveryReport>, Status> {
+    let event_id = event_id
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| Status::BadRequest)?;
+    let report = recovery_report(db, event_id)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    Ok(Json(report))
+}
+
+#[get("/email_suppressions")]
+pub async fn get_email_suppressions(
+    db: &State<Database>,
+    _admin: AdminAuth
+) -> Result<Json<Vec<EmailSuppression>>, Status> {
+    let suppressions = find_suppressions(db)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    Ok(Json(suppressions))
+}
+
+/// Izinkan lagi pengiriman ke alamat yang sebelumnya bounce/complaint (mis. setelah diperbaiki pembeli).
+#[delete("/email_suppressions/<email>")]
+pub async fn delete_email_suppression(
+    db: &State<Database>,
+    _admin: AdminAuth,
+    email: &str
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let removed = remove_suppression(db, email)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+    if !removed {
+        return Err(Custom(Status::NotFound, Json(json!({"error": "Email is not suppressed"}))));
     }
+    Ok(Json(json!({"message": "Suppression removed"})))
 }
 
 pub fn routes() -> Vec<rocket::Route> {
-    routes![me, login, admin_get_events, create_event, get_orders, send_ticket]
+    routes![
+        me,
+        login,
+        get_staff,
+        create_staff,
+        admin_get_events,
+        create_event,
+        clone_event,
+        publish_event,
+        get_event_templates,
+        create_event_template,
+        save_event_as_template,
+        create_event_from_template,
+        delete_event_template,
+        get_email_templates,
+        save_email_template,
+        delete_email_template,
+        preview_email_template,
+        upload_event_poster,
+        upload_event_gallery_image,
+        delete_event_gallery_image,
+        update_event_access,
+        update_ticket_layout,
+        update_entry_rules,
+        update_ticket_delivery,
+        get_access_codes,
+        create_access_code,
+        delete_access_code,
+        get_orders,
+        get_order_tickets,
+        get_event_tickets,
+        void_ticket,
+        send_ticket,
+        get_email_outbox,
+        retry_outbox_email,
+        get_email_suppressions,
+        delete_email_suppression,
+        create_announcement,
+        get_announcements,
+        get_checkout_recovery_report
+    ]
 }
@@ -1,2 +1,3 @@
 pub mod admin;
-pub mod public;
\ No newline at end of file
+pub mod public;
+pub mod staff;
\ No newline at end of file
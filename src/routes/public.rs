@@ -1,19 +1,47 @@
-use rocket::{get, post, routes, State, http::Status, response::status::Custom};
+use rocket::{get, post, routes, State, http::{ContentType, RawStr, Status}, response::{status::Custom, Redirect}};
 use rocket::serde::json::Json;
-use mongodb::{bson::{doc, oid::ObjectId}, Database, Collection};
+use mongodb::{bson::{doc, oid::ObjectId, Document}, Database, Collection, options::{FindOptions, FindOneAndUpdateOptions, ReturnDocument}};
 use uuid::Uuid;
 use serde_json::json;
 use futures::TryStreamExt;
 use anyhow::Result;
 
-use crate::models::{event::Event, order::{Order, CreateOrderPayload, OrderStatus}};
-use crate::utils::midtrans::create_midtrans_transaction;
+use crate::models::email_template::EmailTemplateKind;
+use crate::models::{event::{Event, EventStatus, EventVisibility, CategoryCount, TicketDelivery, DEFAULT_TIMEZONE}, order::{Order, CreateOrderPayload, NotificationChannel, OrderStatus, ResendTicketsPayload}, ticket::{Ticket, TicketStatus}};
+use crate::utils::access::{find_usable_access_code, redeem_access_code, release_access_code};
+use crate::utils::delivery::{deliver_order_tickets, resend_buyer_tickets, send_order_email, AUTO_DELIVERY_MESSAGE};
+use crate::utils::email_webhook::{apply_resend_event, verify_webhook_signature, ResendWebhookEvent, WebhookSignature};
+use crate::utils::feed::{AtomEntry, AtomFeed};
+use crate::utils::ical::{event_invite, CalendarEvent, ICalendar};
+use crate::utils::midtrans::{create_midtrans_transaction, fetch_transaction_status, verify_notification_signature};
+use crate::utils::notifier::{order_created_text, Notifier};
+use crate::utils::outbox::EmailOutbox;
+use crate::utils::pdf::{render_ticket_pdf, TicketPage};
+use crate::utils::qr::{render_png, render_svg};
+use crate::utils::rate_limit::{ClientAddr, TicketResendLimits};
+use crate::utils::recovery::mark_recovery_converted;
+use crate::utils::signing::TicketSigner;
+use crate::utils::suppressions::normalize_email;
+use crate::utils::tickets::{find_ticket_with_token, issue_tickets_for_order, release_event_tickets, reserve_event_tickets, ticket_qr_payload, void_order_tickets};
+use crate::utils::wallet::{build_pkpass, google_save_url, wallet_links, WalletPasses};
 use crate::config::Config;
 
-#[get("/events")]
-pub async fn get_events(db: &State<Database>) -> Result<Json<Vec<Event>>, Status> {
+#[get("/events?<category>&<tag>")]
+pub async fn get_events(
+    db: &State<Database>,
+    category: Option<&str>,
+    tag: Option<&str>,
+) -> Result<Json<Vec<Event>>, Status> {
     let collection: Collection<Event> = db.collection("events");
-    let mut cursor = collection.find(doc! {}, None).await.map_err(|_| Status::InternalServerError)?;
+    let mut filter = Event::listed_filter();
+    if let Some(category) = category {
+        filter.insert("category", category.trim().to_lowercase());
+    }
+    if let Some(tag) = tag {
+        filter.insert("tags", tag.trim().to_lowercase());
+    }
+
+    let mut cursor = collection.find(filter, None).await.map_err(|_| Status::InternalServerError)?;
     let mut events = Vec::new();
     while let Some(event) = cursor.try_next().await.map_err(|_| Status::InternalServerError)? {
         events.push(event);
@@ -21,22 +49,172 @@ pub async fn get_events(db: &State<Database>) -> Result<Json<Vec<Event>>, Status
     Ok(Json(events))
 }
 
-#[get("/events/<id>")]
-pub async fn get_event(db: &State<Database>, id: &str) -> Result<Json<Event>, Status> {
+#[get("/events/categories")]
+pub async fn get_event_categories(db: &State<Database>) -> Result<Json<Vec<CategoryCount>>, Status> {
+    let collection: Collection<Event> = db.collection("events");
+    let mut listed = Event::listed_filter();
+    listed.insert("category", doc! { "$type": "string" });
+    let pipeline = vec![
+        doc! { "$match": listed },
+        doc! { "$group": { "_id": "$category", "count": { "$sum": 1 } } },
+        doc! { "$project": { "_id": 0, "category": "$_id", "count": 1 } },
+        doc! { "$sort": { "category": 1 } },
+    ];
+
+    let mut cursor = collection.aggregate(pipeline, None).await.map_err(|_| Status::InternalServerError)?;
+    let mut categories = Vec::new();
+    while let Some(document) = cursor.try_next().await.map_err(|_| Status::InternalServerError)? {
+        let count: CategoryCount = bson::from_document(document).map_err(|_| Status::InternalServerError)?;
+        categories.push(count);
+    }
+    Ok(Json(categories))
+}
+
+const MAX_TICKETS_PER_ORDER: i32 = 10;
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+async fn find_feed_events(
+    db: &Database,
+    category: Option<&str>,
+    venue: Option<&str>,
+) -> Result<Vec<Event>, Status> {
     let collection: Collection<Event> = db.collection("events");
-    let object_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
-    let event = collection.find_one(doc! {"_id": object_id}, None).await.map_err(|_| Status::InternalServerError)?;
+    let mut filter = Event::listed_filter();
+    if let Some(category) = category {
+        filter.insert("category", category.trim().to_lowercase());
+    }
+    if let Some(venue) = venue.map(str::trim).filter(|v| !v.is_empty()) {
+        filter.insert("location", doc! {"$regex": escape_regex(venue), "$options": "i"});
+    }
+
+    let find_options = FindOptions::builder().sort(doc! {"date": 1}).build();
+    let mut cursor = collection.find(filter, find_options).await.map_err(|_| Status::InternalServerError)?;
+    let mut events = Vec::new();
+    while let Some(event) = cursor.try_next().await.map_err(|_| Status::InternalServerError)? {
+        events.push(event);
+    }
+    Ok(events)
+}
+
+fn feed_query(category: Option<&str>, venue: Option<&str>) -> String {
+    let params: Vec<String> = [("category", category), ("venue", venue)]
+        .iter()
+        .filter_map(|(key, value)| value.map(|v| format!("{}={}", key, RawStr::new(v).percent_encode())))
+        .collect();
+    if params.is_empty() { String::new() } else { format!("?{}", params.join("&")) }
+}
+
+#[get("/events.ics?<category>&<venue>")]
+pub async fn get_events_ical(
+    db: &State<Database>,
+    config: &State<Config>,
+    category: Option<&str>,
+    venue: Option<&str>,
+) -> Result<(ContentType, String), Status> {
+    let events = find_feed_events(db, category, venue).await?;
+    // UID stabil: diturunkan dari ObjectId event + host aplikasi
+    let host = config.public_host();
+
+    let mut calendar = ICalendar::new("Ticketing App Events", DEFAULT_TIMEZONE);
+    for event in &events {
+        let Some(id) = event.id else { continue };
+        calendar.add_event(&CalendarEvent {
+            uid: format!("event-{}@{}", id.to_hex(), host),
+            summary: event.name.clone(),
+            description: event.description.clone(),
+            location: event.location.clone(),
+            start: event.date,
+            url: Some(event.public_url(&config.public_base_url)),
+            timezone: None,
+            organizer: None,
+            alarm_before: None,
+        });
+    }
+
+    Ok((ContentType::Calendar, calendar.finish()))
+}
+
+#[get("/events.atom?<category>&<venue>")]
+pub async fn get_events_atom(
+    db: &State<Database>,
+    config: &State<Config>,
+    category: Option<&str>,
+    venue: Option<&str>,
+) -> Result<(ContentType, String), Status> {
+    let events = find_feed_events(db, category, venue).await?;
+    let self_url = format!("{}/api/events.atom{}", config.public_base_url, feed_query(category, venue));
+
+    let mut feed = AtomFeed::new(
+        format!("{}/api/events.atom", config.public_base_url),
+        "Ticketing App Events".to_string(),
+        self_url,
+    );
+    for event in &events {
+        let Some(id) = event.id else { continue };
+        let local_date = event.local_date();
+        feed.add_entry(AtomEntry {
+            id: format!("{}/api/events/{}", config.public_base_url, id.to_hex()),
+            title: event.name.clone(),
+            url: event.public_url(&config.public_base_url),
+            summary: format!("{} — {}", local_date.format("%d %B %Y %H:%M %Z"), event.location),
+            content: format!(
+                "{}\n\nTanggal: {}\nLokasi: {}",
+                event.description,
+                local_date.format("%d %B %Y %H:%M %Z"),
+                event.location
+            ),
+            updated: event.last_updated(),
+            categories: event.category.iter().chain(event.tags.iter()).cloned().collect(),
+        });
+    }
+
+    Ok((ContentType::new("application", "atom+xml"), feed.finish()))
+}
+
+// `id` bisa berupa ObjectId atau slug event
+#[get("/events/<id>?<code>")]
+pub async fn get_event(db: &State<Database>, id: &str, code: Option<&str>) -> Result<Json<Event>, Status> {
+    let collection: Collection<Event> = db.collection("events");
+    let filter = match ObjectId::parse_str(id) {
+        Ok(object_id) => doc! {"_id": object_id},
+        Err(_) => doc! {"slug": id},
+    };
+    let event = collection.find_one(filter, None).await.map_err(|_| Status::InternalServerError)?;
     
-    match event {
-        Some(e) => Ok(Json(e)),
-        None => Err(Status::NotFound),
+    let event = event
+        .filter(|e| e.status == EventStatus::Published)
+        .ok_or(Status::NotFound)?;
+
+    // Event private disembunyikan kecuali membawa access code yang valid
+    if event.visibility == EventVisibility::Private {
+        let code = code.ok_or(Status::NotFound)?;
+        let event_id = event.id.ok_or(Status::NotFound)?;
+        find_usable_access_code(db, &event_id, code)
+            .await
+            .map_err(|_| Status::InternalServerError)?
+            .ok_or(Status::NotFound)?;
     }
+
+    Ok(Json(event))
 }
 
 #[post("/orders", data = "<payload>")]
 pub async fn create_order(
     db: &State<Database>, 
     config: &State<Config>, 
+    wallet: &State<WalletPasses>,
+    outbox: &State<EmailOutbox>,
+    notifier: &State<Notifier>,
     payload: Json<CreateOrderPayload>
 ) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
     let event_collection: Collection<Event> = db.collection("events");
@@ -49,10 +227,57 @@ pub async fn create_order(
         .await
         .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
     
-    let event = event.ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
+    let event = event
+        .filter(|e| e.status == EventStatus::Published)
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))?;
 
-    if event.available_tickets <= 0 {
-        return Err(Custom(Status::BadRequest, Json(json!({"error": "No tickets available"}))));
+    let tier = payload.tier.clone().unwrap_or_else(|| event.default_tier().to_string());
+    if event.tier_price(&tier).is_none() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "Invalid ticket tier"}))));
+    }
+
+    let quantity = payload.quantity.unwrap_or(1);
+    if !(1..=MAX_TICKETS_PER_ORDER).contains(&quantity) {
+        return Err(Custom(Status::BadRequest, Json(json!({
+            "error": format!("Quantity must be between 1 and {}", MAX_TICKETS_PER_ORDER)
+        }))));
+    }
+
+    if payload.notification_channel != NotificationChannel::Email && notifier.normalize_phone(&payload.buyer_phone).is_none() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "Invalid phone number for SMS/WhatsApp notifications"}))));
+    }
+
+    let redeemed_code = if event.requires_access_code(chrono::Utc::now()) {
+        let code = payload.access_code.as_deref()
+            .ok_or_else(|| Custom(Status::Forbidden, Json(json!({"error": "Access code required"}))))?;
+        let access_code = find_usable_access_code(db, &event_object_id, code)
+            .await
+            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+            .ok_or_else(|| Custom(Status::Forbidden, Json(json!({"error": "Invalid or expired access code"}))))?;
+        let access_code_id = access_code.id
+            .ok_or_else(|| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+
+        let redeemed = redeem_access_code(db, &access_code_id)
+            .await
+            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+        if !redeemed {
+            return Err(Custom(Status::Forbidden, Json(json!({"error": "Invalid or expired access code"}))));
+        }
+        Some((access_code_id, access_code.code))
+    } else {
+        None
+    };
+
+    // Kuota dikurangi saat order dibuat dan dikembalikan jika order gagal
+    let reserved = reserve_event_tickets(db, event_object_id, quantity).await;
+    if !matches!(reserved, Ok(true)) {
+        if let Some((access_code_id, _)) = &redeemed_code {
+            release_access_code(db, access_code_id).await;
+        }
+        return Err(match reserved {
+            Ok(_) => Custom(Status::BadRequest, Json(json!({"error": "No tickets available"}))),
+            Err(_) => Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))),
+        });
     }
 
     let midtrans_order_id = format!("ORDER-{}", Uuid::new_v4());
@@ -62,18 +287,52 @@ pub async fn create_order(
         buyer_name: payload.buyer_name.clone(),
         buyer_email: payload.buyer_email.clone(),
         buyer_phone: payload.buyer_phone.clone(),
+        tier,
+        quantity,
         status: OrderStatus::Pending,
         midtrans_order_id: midtrans_order_id.clone(),
         created_at: chrono::Utc::now(),
+        access_code: redeemed_code.as_ref().map(|(_, code)| code.clone()),
+        email_bounced: false,
+        payment_url: None,
+        recovery_status: None,
+        recovery_email_sent_at: None,
+        recovered_at: None,
+        notification_channel: payload.notification_channel,
     };
 
     let insert_result = match order_collection.insert_one(&new_order, None).await {
         Ok(result) => result,
-        Err(_) => return Err(Custom(Status::InternalServerError, Json(json!({"error": "Failed to create order"})))),
+        Err(_) => {
+            release_event_tickets(db, event_object_id, quantity).await;
+            if let Some((access_code_id, _)) = &redeemed_code {
+                release_access_code(db, access_code_id).await;
+            }
+            return Err(Custom(Status::InternalServerError, Json(json!({"error": "Failed to create order"}))));
+        }
     };
     
     match create_midtrans_transaction(&new_order, &event, &config.midtrans_server_key).await {
         Ok(midtrans_res) => {
+            let _ = order_collection
+                .update_one(
+                    doc! {"_id": &insert_result.inserted_id},
+                    doc! {"$set": {"payment_url": &midtrans_res.redirect_url}},
+                    None,
+                )
+                .await;
+            notifier.notify(&new_order, order_created_text(&event, &new_order, &midtrans_res.redirect_url));
+            let saved_order = Order {
+                id: insert_result.inserted_id.as_object_id(),
+                payment_url: Some(midtrans_res.redirect_url.clone()),
+                ..new_order
+            };
+            if let Err(e) = send_order_email(
+                db, config, wallet, outbox, &saved_order,
+                EmailTemplateKind::OrderConfirmation, "", saved_order.payment_url.as_deref(),
+            ).await {
+                println!("❌ Failed to queue order confirmation for {}: {:?}", saved_order.midtrans_order_id, e);
+            }
             Ok(Json(json!({
                 "order_id": insert_result.inserted_id.as_object_id().unwrap().to_hex(),
                 "midtrans_token": midtrans_res.token,
@@ -82,6 +341,10 @@ pub async fn create_order(
         }
         Err(e) => {
             let _ = order_collection.delete_one(doc! {"_id": insert_result.inserted_id}, None).await;
+            release_event_tickets(db, event_object_id, quantity).await;
+            if let Some((access_code_id, _)) = &redeemed_code {
+                release_access_code(db, access_code_id).await;
+            }
 
             Err(Custom(
                 Status::InternalServerError, 
@@ -94,14 +357,338 @@ pub async fn create_order(
     }
 }
 
+async fn find_public_ticket(db: &Database, id: &str, token: Option<&str>) -> Result<Ticket, Status> {
+    let token = token.ok_or(Status::Unauthorized)?;
+    find_ticket_with_token(db, id, token)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)
+}
+
+#[get("/tickets/signing_keys")]
+pub fn get_ticket_signing_keys(signer: &State<TicketSigner>) -> Json<serde_json::Value> {
+    Json(json!({
+        "active_kid": signer.active_kid(),
+        "keys": signer.public_keys(),
+    }))
+}
+
+#[get("/tickets/<id>?<token>")]
+pub async fn get_ticket(db: &State<Database>, id: &str, token: Option<&str>) -> Result<Json<Ticket>, Status> {
+    let ticket = find_public_ticket(db, id, token).await?;
+    Ok(Json(ticket))
+}
+
+#[get("/tickets/<id>/qr.png?<token>")]
+pub async fn get_ticket_qr_png(
+    db: &State<Database>,
+    signer: &State<TicketSigner>,
+    id: &str,
+    token: Option<&str>,
+) -> Result<(ContentType, Vec<u8>), Status> {
+    let ticket = find_public_ticket(db, id, token).await?;
+    if ticket.status == TicketStatus::Void {
+        return Err(Status::Gone);
+    }
+
+    let png = render_png(&ticket_qr_payload(signer, &ticket)).map_err(|_| Status::InternalServerError)?;
+    Ok((ContentType::PNG, png))
+}
+
+#[get("/tickets/<id>/qr.svg?<token>")]
+pub async fn get_ticket_qr_svg(
+    db: &State<Database>,
+    signer: &State<TicketSigner>,
+    id: &str,
+    token: Option<&str>,
+) -> Result<(ContentType, String), Status> {
+    let ticket = find_public_ticket(db, id, token).await?;
+    if ticket.status == TicketStatus::Void {
+        return Err(Status::Gone);
+    }
+
+    let svg = render_svg(&ticket_qr_payload(signer, &ticket)).map_err(|_| Status::InternalServerError)?;
+    Ok((ContentType::SVG, svg))
+}
+
+#[get("/tickets/<id>/ticket.pdf?<token>")]
+pub async fn get_ticket_pdf(
+    db: &State<Database>,
+    signer: &State<TicketSigner>,
+    id: &str,
+    token: Option<&str>,
+) -> Result<(ContentType, Vec<u8>), Status> {
+    let ticket = find_public_ticket(db, id, token).await?;
+    if ticket.status == TicketStatus::Void {
+        return Err(Status::Gone);
+    }
+
+    let order = db
+        .collection::<Order>("orders")
+        .find_one(doc! {"_id": ticket.order_id}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)?;
+    let event = db
+        .collection::<Event>("events")
+        .find_one(doc! {"_id": ticket.event_id}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)?;
+
+    let page = TicketPage { ticket: &ticket, qr_payload: ticket_qr_payload(signer, &ticket) };
+    let pdf = render_ticket_pdf(&event, &order, &[page]).map_err(|_| Status::InternalServerError)?;
+    Ok((ContentType::PDF, pdf))
+}
+
+async fn find_ticket_event(db: &Database, ticket: &Ticket) -> Result<Event, Status> {
+    db.collection::<Event>("events")
+        .find_one(doc! {"_id": ticket.event_id}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)
+}
+
+#[get("/tickets/<id>/event.ics?<token>")]
+pub async fn get_ticket_calendar(
+    db: &State<Database>,
+    config: &State<Config>,
+    id: &str,
+    token: Option<&str>,
+) -> Result<(ContentType, String), Status> {
+    let ticket = find_public_ticket(db, id, token).await?;
+    if ticket.status == TicketStatus::Void {
+        return Err(Status::Gone);
+    }
+    let event = find_ticket_event(db, &ticket).await?;
+    Ok((ContentType::Calendar, event_invite(config, &event)))
+}
+
+#[get("/tickets/<id>/wallet?<token>")]
+pub async fn get_ticket_wallet_links(
+    db: &State<Database>,
+    config: &State<Config>,
+    wallet: &State<WalletPasses>,
+    id: &str,
+    token: Option<&str>,
+) -> Result<Json<serde_json::Value>, Status> {
+    let ticket = find_public_ticket(db, id, token).await?;
+    if ticket.status == TicketStatus::Void {
+        return Err(Status::Gone);
+    }
+    Ok(Json(json!(wallet_links(config, wallet, &ticket))))
+}
+
+#[get("/tickets/<id>/wallet.pkpass?<token>")]
+pub async fn get_ticket_pkpass(
+    db: &State<Database>,
+    signer: &State<TicketSigner>,
+    wallet: &State<WalletPasses>,
+    id: &str,
+    token: Option<&str>,
+) -> Result<(ContentType, Vec<u8>), Status> {
+    if wallet.apple.is_none() {
+        return Err(Status::NotFound);
+    }
+    let ticket = find_public_ticket(db, id, token).await?;
+    if ticket.status == TicketStatus::Void {
+        return Err(Status::Gone);
+    }
+    let event = find_ticket_event(db, &ticket).await?;
+
+    let pkpass = build_pkpass(wallet, &event, &ticket, &ticket_qr_payload(signer, &ticket)).map_err(|e| {
+        eprintln!("Failed to build pkpass for ticket {}: {:?}", ticket.code, e);
+        Status::InternalServerError
+    })?;
+    Ok((ContentType::new("application", "vnd.apple.pkpass"), pkpass))
+}
+
+#[get("/tickets/<id>/wallet/google?<token>")]
+pub async fn get_ticket_google_wallet(
+    db: &State<Database>,
+    config: &State<Config>,
+    signer: &State<TicketSigner>,
+    wallet: &State<WalletPasses>,
+    id: &str,
+    token: Option<&str>,
+) -> Result<Redirect, Status> {
+    if wallet.google.is_none() {
+        return Err(Status::NotFound);
+    }
+    let ticket = find_public_ticket(db, id, token).await?;
+    if ticket.status == TicketStatus::Void {
+        return Err(Status::Gone);
+    }
+    let event = find_ticket_event(db, &ticket).await?;
+
+    let url = google_save_url(&config.public_base_url, wallet, &event, &ticket, &ticket_qr_payload(signer, &ticket)).map_err(|e| {
+        eprintln!("Failed to sign Google Wallet pass for ticket {}: {:?}", ticket.code, e);
+        Status::InternalServerError
+    })?;
+    Ok(Redirect::to(url))
+}
+
+/// Pembeli meminta tiketnya dikirim ulang. Responsnya selalu sama, dan pencarian order berjalan
+/// di background, supaya endpoint ini tidak bisa dipakai menebak email yang pernah membeli.
+#[post("/tickets/resend", data = "<payload>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn resend_tickets(
+    db: &State<Database>,
+    config: &State<Config>,
+    signer: &State<TicketSigner>,
+    wallet: &State<WalletPasses>,
+    outbox: &State<EmailOutbox>,
+    notifier: &State<Notifier>,
+    limits: &State<TicketResendLimits>,
+    client: ClientAddr,
+    payload: Json<ResendTicketsPayload>
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let email = normalize_email(&payload.email);
+    if !email.contains('@') {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "Invalid email"}))));
+    }
+
+    // Alamat klien hanya tidak diketahui untuk koneksi non-TCP (mis. client lokal di test);
+    // request seperti itu tidak digabung ke satu bucket kosong
+    let ip_allowed = client.0.is_none_or(|ip| limits.per_ip.check(&ip.to_string()));
+    if !ip_allowed || !limits.per_email.check(&email) {
+        return Err(Custom(Status::TooManyRequests, Json(json!({"error": "Too many requests, please try again later"}))));
+    }
+
+    let order_number = payload.order_number.clone().filter(|n| !n.trim().is_empty());
+    let (db, config, signer, wallet) = (db.inner().clone(), config.inner().clone(), signer.inner().clone(), wallet.inner().clone());
+    let (outbox, notifier) = (outbox.inner().clone(), notifier.inner().clone());
+    rocket::tokio::spawn(async move {
+        match resend_buyer_tickets(&db, &config, &signer, &wallet, &outbox, &notifier, &email, order_number.as_deref()).await {
+            Ok(0) => {}
+            Ok(queued) => println!("📧 Buyer resend queued {} ticket email(s)", queued),
+            Err(e) => println!("❌ Buyer ticket resend failed: {:?}", e),
+        }
+    });
+
+    Ok(Json(json!({"message": "If we find tickets for this email, they will be sent shortly"})))
+}
+
+// Email tiket dikirim di background supaya webhook Midtrans langsung dibalas
+async fn spawn_auto_delivery(
+    db: &Database,
+    config: &Config,
+    signer: &TicketSigner,
+    wallet: &WalletPasses,
+    outbox: &EmailOutbox,
+    notifier: &Notifier,
+    order: Order,
+) {
+    let event = match db.collection::<Event>("events").find_one(doc! {"_id": order.event_id}, None).await {
+        Ok(Some(event)) => event,
+        Ok(None) => return,
+        Err(e) => {
+            println!("❌ Failed to load event for order {}: {:?}", order.midtrans_order_id, e);
+            return;
+        }
+    };
+    if event.ticket_delivery != TicketDelivery::Auto {
+        println!("Ticket delivery for order {} is manual", order.midtrans_order_id);
+        return;
+    }
+
+    let (db, config, signer, wallet) = (db.clone(), config.clone(), signer.clone(), wallet.clone());
+    let (outbox, notifier) = (outbox.clone(), notifier.clone());
+    rocket::tokio::spawn(async move {
+        match deliver_order_tickets(&db, &config, &signer, &wallet, &outbox, &notifier, &order, None, AUTO_DELIVERY_MESSAGE).await {
+            Ok(outbox_id) => println!("📧 Ticket email for order {} queued ({})", order.midtrans_order_id, outbox_id),
+            Err(e) => println!("❌ Failed to queue tickets for order {}: {:?}", order.midtrans_order_id, e),
+        }
+    });
+}
+
+// Notifikasi Midtrans memakai `midtrans_order_id`; order lama masih bisa memakai `_id`
+fn order_filters(order_id: &str, from: &[&str]) -> Vec<(&'static str, Document)> {
+    let mut filters = Vec::new();
+    if let Ok(object_id) = ObjectId::parse_str(order_id) {
+        filters.push(("_id", doc! { "_id": object_id, "status": { "$in": from } }));
+    }
+    filters.push(("midtrans_order_id", doc! { "midtrans_order_id": order_id, "status": { "$in": from } }));
+    filters
+}
+
+/// Ubah status order hanya jika status saat ini ada di `from`; `None` jika tidak ada transisi.
+async fn transition_order(collection: &Collection<Order>, order_id: &str, from: &[&str], to: &OrderStatus) -> Option<Order> {
+    let update = doc! { "$set": { "status": to.to_string() } };
+    let options = FindOneAndUpdateOptions::builder()
+        .return_document(ReturnDocument::After)
+        .build();
+
+    for (field, filter) in order_filters(order_id, from) {
+        match collection.find_one_and_update(filter, update.clone(), options.clone()).await {
+            Ok(Some(order)) => {
+                println!("✅ Order updated successfully by {}", field);
+                return Some(order);
+            }
+            Ok(None) => {
+                println!("No order transitioned by {}", field);
+            }
+            Err(e) => {
+                println!("❌ Database update error by {}: {:?}", field, e);
+            }
+        }
+    }
+    None
+}
+
+async fn is_failed_order(collection: &Collection<Order>, order_id: &str) -> bool {
+    for (_, filter) in order_filters(order_id, &["failed"]) {
+        if let Ok(Some(_)) = collection.find_one(filter, None).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// Refund penuh dari Midtrans: order lunas menjadi `refunded`, tiketnya di-void, kuota dikembalikan,
+/// dan pembeli menerima email `refund`. Refund sebagian tidak mengubah order.
+async fn refund_order(
+    db: &Database,
+    config: &Config,
+    wallet: &WalletPasses,
+    outbox: &EmailOutbox,
+    collection: &Collection<Order>,
+    order_id: &str,
+) {
+    let Some(order) = transition_order(collection, order_id, &["paid", "sent"], &OrderStatus::Refunded).await else {
+        println!("Refund ignored for order {}: not paid or already refunded", order_id);
+        return;
+    };
+    if let Some(id) = order.id {
+        match void_order_tickets(db, id).await {
+            Ok(voided) => println!("🎟️ Voided {} ticket(s) for refunded order {}", voided, order_id),
+            Err(e) => println!("❌ Failed to void tickets for refunded order {}: {:?}", order_id, e),
+        }
+    }
+    release_event_tickets(db, order.event_id, order.quantity).await;
+    if let Err(e) = send_order_email(db, config, wallet, outbox, &order, EmailTemplateKind::Refund, "", None).await {
+        println!("❌ Failed to queue refund email for order {}: {:?}", order_id, e);
+    }
+}
+
 #[post("/orders/notify", data = "<payload>")]
 pub async fn midtrans_webhook(
     db: &State<Database>, 
-    _config: &State<Config>, 
+    config: &State<Config>, 
+    signer: &State<TicketSigner>,
+    wallet: &State<WalletPasses>,
+    outbox: &State<EmailOutbox>,
+    notifier: &State<Notifier>,
     payload: Json<serde_json::Value>
 ) -> Status {
     println!("=== MIDTRANS WEBHOOK RECEIVED ===");
     println!("Payload: {:?}", payload);
+
+    // Tanpa tanda tangan yang valid notifikasi bisa dipalsukan untuk menerbitkan tiket
+    if !verify_notification_signature(&payload, &config.midtrans_server_key) {
+        println!("❌ Invalid Midtrans signature");
+        return Status::Unauthorized;
+    }
     
     if let Some(order_id) = payload.get("order_id").and_then(|v| v.as_str()) {
         println!("Processing order_id: {}", order_id);
@@ -110,6 +697,11 @@ pub async fn midtrans_webhook(
             println!("Transaction status: {}", transaction_status);
             
             let collection: Collection<Order> = db.collection("orders");
+
+            if transaction_status == "refund" {
+                refund_order(db, config, wallet, outbox, &collection, order_id).await;
+                return Status::Ok;
+            }
             
             let new_status = if transaction_status == "settlement" {
                 OrderStatus::Paid
@@ -121,34 +713,49 @@ pub async fn midtrans_webhook(
             };
 
             println!("New status to set: {:?}", new_status);
-            let update = doc! { "$set": { "status": new_status.to_string() } };
-            
-            if let Ok(object_id) = ObjectId::parse_str(order_id) {
-                let filter = doc! { "_id": object_id };
-                
-                match collection.update_one(filter, update.clone(), None).await {
-                    Ok(result) => {
-                        println!("Update by _id - matched: {}, modified: {}", 
-                                 result.matched_count, result.modified_count);
-                        if result.matched_count > 0 {
-                            println!("✅ Order updated successfully by _id");
-                            return Status::Ok;
+            // Hanya transisi yang sah: order yang sudah paid/sent tidak diturunkan lagi
+            let mut updated_order = transition_order(&collection, order_id, &["pending"], &new_status).await;
+
+            // Order yang sudah gagal hanya boleh menjadi paid jika Status API Midtrans mengonfirmasi settlement
+            if updated_order.is_none() && new_status == OrderStatus::Paid && is_failed_order(&collection, order_id).await {
+                match fetch_transaction_status(order_id, &config.midtrans_server_key).await {
+                    Ok(status) if status == "settlement" => {
+                        updated_order = transition_order(&collection, order_id, &["failed"], &new_status).await;
+                        // Kuotanya sudah dikembalikan saat order gagal, jadi diambil lagi
+                        if let Some(order) = &updated_order {
+                            if !matches!(reserve_event_tickets(db, order.event_id, order.quantity).await, Ok(true)) {
+                                println!("⚠️ Event sold out while order {} was failed; paid anyway", order_id);
+                            }
                         }
                     }
-                    Err(e) => {
-                        println!("❌ Database update error by _id: {:?}", e);
-                    }
+                    Ok(status) => println!("Midtrans status for failed order {} is {}; not marking paid", order_id, status),
+                    Err(e) => println!("❌ Failed to check Midtrans status for order {}: {:?}", order_id, e),
                 }
             }
-            
-            let filter = doc! { "midtrans_order_id": order_id };
-            match collection.update_one(filter, update, None).await {
-                Ok(result) => {
-                    println!("Update by midtrans_order_id - matched: {}, modified: {}", 
-                             result.matched_count, result.modified_count);
+
+            if let Some(order) = updated_order.as_ref().filter(|o| o.status == OrderStatus::Failed) {
+                release_event_tickets(db, order.event_id, order.quantity).await;
+                let message = match transaction_status {
+                    "deny" => "Pembayaran ditolak oleh penyedia pembayaran.",
+                    "expire" => "Batas waktu pembayaran telah habis.",
+                    _ => "Pembayaran dibatalkan.",
+                };
+                if let Err(e) = send_order_email(db, config, wallet, outbox, order, EmailTemplateKind::Cancellation, message, None).await {
+                    println!("❌ Failed to queue cancellation email for order {}: {:?}", order_id, e);
                 }
-                Err(e) => {
-                    println!("❌ Database update error by midtrans_order_id: {:?}", e);
+            }
+
+            // Hanya notifikasi terverifikasi yang benar-benar memindahkan order ke `paid` yang sampai
+            // di sini; settlement ulangan untuk order yang sama tidak mengirim email tiket lagi
+            if let Some(order) = updated_order.filter(|o| o.status == OrderStatus::Paid) {
+                mark_recovery_converted(db, &order).await;
+                match issue_tickets_for_order(db, &order).await {
+                    Ok(tickets) => {
+                        println!("🎟️ Issued {} ticket(s) for order {}", tickets.len(), order_id);
+                        spawn_auto_delivery(db, config, signer, wallet, outbox, notifier, order).await;
+                    }
+                    // Admin masih bisa mengirim tiket lewat send_ticket (tiket diterbitkan ulang di sana)
+                    Err(e) => println!("❌ Failed to issue tickets for order {}: {:?}", order_id, e),
                 }
             }
         }
@@ -157,6 +764,52 @@ pub async fn midtrans_webhook(
     Status::Ok
 }
 
+/// Webhook status pengiriman dari Resend (delivered, bounced, complained, opened).
+#[post("/email/webhook", data = "<body>")]
+pub async fn resend_webhook(
+    db: &State<Database>,
+    config: &State<Config>,
+    signature: WebhookSignature,
+    body: String
+) -> Result<Status, Custom<Json<serde_json::Value>>> {
+    let secret = config.resend_webhook_secret.as_deref()
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Webhook not configured"}))))?;
+    verify_webhook_signature(secret, &signature, &body, chrono::Utc::now())
+        .map_err(|e| Custom(Status::Unauthorized, Json(json!({"error": e.to_string()}))))?;
+
+    let event: ResendWebhookEvent = serde_json::from_str(&body)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid webhook payload"}))))?;
+    match apply_resend_event(db, &event).await {
+        Ok(true) => println!("📬 Resend {} for {}", event.event_type, event.data.email_id),
+        Ok(false) => println!("Resend event ignored: {}", event.event_type),
+        Err(e) => {
+            println!("❌ Failed to apply Resend event {}: {:?}", event.event_type, e);
+            // 500 supaya Svix mengirim ulang event nanti
+            return Err(Custom(Status::InternalServerError, Json(json!({"error": "Failed to process webhook"}))));
+        }
+    }
+    Ok(Status::Ok)
+}
+
 pub fn routes() -> Vec<rocket::Route> {
-    routes![get_events, get_event, create_order, midtrans_webhook]
+    routes![
+        get_events,
+        get_event_categories,
+        get_events_ical,
+        get_events_atom,
+        get_event,
+        create_order,
+        get_ticket_signing_keys,
+        get_ticket,
+        get_ticket_qr_png,
+        get_ticket_qr_svg,
+        get_ticket_pdf,
+        get_ticket_calendar,
+        get_ticket_wallet_links,
+        get_ticket_pkpass,
+        get_ticket_google_wallet,
+        resend_tickets,
+        midtrans_webhook,
+        resend_webhook
+    ]
 }
@@ -0,0 +1,726 @@
+use serde_json::json;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use sha2::{Digest, Sha256};
+
+use rocket::{get, post, routes, State, Shutdown, http::Status, response::status::Custom};
+use rocket::response::stream::{Event as SseEvent, EventStream};
+use rocket::tokio::{select, sync::broadcast::error::RecvError};
+use rocket::serde::json::Json;
+use mongodb::{bson::{doc, oid::ObjectId}, Database, Collection, options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument}};
+
+use crate::models::checkin::{AttendanceStats, AttendanceUpdate, CheckIn, CheckInPayload, ManifestTicket, ScanBatchPayload, ScanDirection, UndoCheckInPayload};
+use crate::models::event::{EntryRules, Event, ReentryPolicy};
+use crate::models::ticket::{Ticket, TicketStatus};
+use crate::utils::{auth::{StaffAuth, SupervisorAuth}, signing::TicketSigner, tickets::find_tickets, time::stored_time};
+use crate::utils::attendance::{arrival_series, attendance_stats, current_occupancy, find_no_shows, AttendanceFeed};
+
+// Statistik tidak lagi dihitung per subscriber; `AttendanceStatsWorker` yang mengirimkannya
+enum LiveMessage {
+    Scan(AttendanceUpdate),
+    Stats(AttendanceStats),
+}
+
+enum ScanLookupError {
+    InvalidToken(String),
+    NotFound,
+    Database,
+}
+
+/// Hasil penerapan satu scan (masuk/keluar) terhadap status tiket.
+enum ScanOutcome {
+    Admitted(Ticket),
+    Exited(Ticket),
+    Rejected { result: &'static str, ticket: Ticket },
+}
+
+/// Cari tiket dari hasil scan: token bertanda tangan (mengandung '.') atau kode tiket biasa.
+async fn lookup_scanned_ticket(
+    db: &Database,
+    signer: &TicketSigner,
+    scan: &str,
+) -> Result<Ticket, ScanLookupError> {
+    let collection: Collection<Ticket> = db.collection("tickets");
+    let scan = scan.trim();
+
+    let filter = if scan.contains('.') {
+        let claims = signer.verify(scan).map_err(|e| ScanLookupError::InvalidToken(e.to_string()))?;
+        let ticket_id = ObjectId::parse_str(&claims.ticket_id)
+            .map_err(|_| ScanLookupError::InvalidToken("Invalid ticket id".to_string()))?;
+        doc! {"_id": ticket_id, "code": claims.code}
+    } else {
+        doc! {"code": scan.to_uppercase()}
+    };
+
+    collection
+        .find_one(filter, None)
+        .await
+        .map_err(|_| ScanLookupError::Database)?
+        .ok_or(ScanLookupError::NotFound)
+}
+
+/// Terapkan aturan re-entry event: masuk pertama selalu diterima, masuk ulang
+/// hanya jika pengunjung sudah scan keluar dan batas masuk belum tercapai.
+async fn apply_scan(
+    collection: &Collection<Ticket>,
+    ticket: Ticket,
+    rules: &EntryRules,
+    direction: ScanDirection,
+    gate: &str,
+    staff_email: &str,
+    at: DateTime<Utc>,
+) -> mongodb::error::Result<ScanOutcome> {
+    let Some(ticket_id) = ticket.id else {
+        return Ok(ScanOutcome::Rejected { result: "invalid", ticket });
+    };
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+
+    if direction == ScanDirection::Out {
+        let exited = collection
+            .find_one_and_update(
+                doc! {"_id": ticket_id, "inside": true},
+                doc! {"$set": {"inside": false}},
+                options
+            )
+            .await?;
+        return Ok(match exited {
+            Some(ticket) => ScanOutcome::Exited(ticket),
+            None => ScanOutcome::Rejected { result: "not_inside", ticket },
+        });
+    }
+
+    let first_entry = collection
+        .find_one_and_update(
+            doc! {"_id": ticket_id, "status": "valid"},
+            doc! {"$set": {
+                "status": "used",
+                "checked_in_at": stored_time(at),
+                "checked_in_gate": gate,
+                "checked_in_by": staff_email,
+                "inside": true,
+                "entry_count": 1,
+            }},
+            options.clone()
+        )
+        .await?;
+    if let Some(ticket) = first_entry {
+        return Ok(ScanOutcome::Admitted(ticket));
+    }
+
+    let mut filter = doc! {"_id": ticket_id, "status": "used", "inside": {"$ne": true}};
+    let reentry_allowed = match rules.reentry {
+        ReentryPolicy::Single => false,
+        ReentryPolicy::Unlimited => true,
+        ReentryPolicy::Limited { max_entries } => {
+            filter.insert("entry_count", doc! {"$lt": max_entries as i64});
+            true
+        }
+    };
+    if reentry_allowed {
+        let reentered = collection
+            .find_one_and_update(
+                filter,
+                doc! {"$set": {"inside": true}, "$inc": {"entry_count": 1}},
+                options
+            )
+            .await?;
+        if let Some(ticket) = reentered {
+            return Ok(ScanOutcome::Admitted(ticket));
+        }
+    }
+
+    // Kalah balapan dengan scan lain atau memang tidak boleh masuk: laporkan kondisi terkini
+    let current = collection.find_one(doc! {"_id": ticket_id}, None).await?.unwrap_or(ticket);
+    let result = if current.status == TicketStatus::Void {
+        "void"
+    } else if !reentry_allowed {
+        "duplicate"
+    } else if current.inside {
+        "already_inside"
+    } else {
+        "entry_limit_reached"
+    };
+    Ok(ScanOutcome::Rejected { result, ticket: current })
+}
+
+fn rejection_message(result: &str) -> &'static str {
+    match result {
+        "duplicate" => "Ticket already used",
+        "already_inside" => "Ticket holder is already inside",
+        "entry_limit_reached" => "Re-entry limit reached",
+        "not_inside" => "Ticket holder is not inside",
+        "void" => "Ticket has been voided",
+        _ => "Ticket rejected",
+    }
+}
+
+fn first_scan(ticket: &Ticket) -> serde_json::Value {
+    json!({
+        "at": ticket.checked_in_at,
+        "gate": ticket.checked_in_gate,
+        "staff_id": ticket.checked_in_by,
+    })
+}
+
+async fn record_checkin(db: &Database, checkin: CheckIn) {
+    if let Err(e) = db.collection::<CheckIn>("checkins").insert_one(&checkin, None).await {
+        eprintln!("Failed to record check-in for ticket {}: {:?}", checkin.code, e);
+    }
+}
+
+async fn find_event(db: &Database, event_id: ObjectId) -> Result<Event, Custom<Json<serde_json::Value>>> {
+    db.collection::<Event>("events")
+        .find_one(doc! {"_id": event_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "Event not found"}))))
+}
+
+/// Hitung okupansi terkini dan kirim peringatan ke dashboard bila mendekati kapasitas venue.
+async fn check_capacity(
+    db: &Database,
+    feed: &AttendanceFeed,
+    event_id: ObjectId,
+    rules: &EntryRules,
+    ticket_id: ObjectId,
+    gate: &str,
+) -> serde_json::Value {
+    let occupancy = match current_occupancy(db, event_id).await {
+        Ok(occupancy) => occupancy,
+        Err(_) => return serde_json::Value::Null,
+    };
+    let alert = rules.capacity_alert_at().is_some_and(|threshold| occupancy >= threshold);
+    if alert {
+        feed.publish(event_id, ticket_id, gate, "capacity_alert");
+    }
+    json!({
+        "occupancy": occupancy,
+        "venue_capacity": rules.venue_capacity,
+        "alert": alert,
+    })
+}
+
+fn outcome_result(outcome: &ScanOutcome) -> &'static str {
+    match outcome {
+        ScanOutcome::Admitted(_) => "admitted",
+        ScanOutcome::Exited(_) => "exited",
+        ScanOutcome::Rejected { result, .. } => result,
+    }
+}
+
+#[post("/checkin", data = "<payload>")]
+pub async fn check_in(
+    db: &State<Database>,
+    signer: &State<TicketSigner>,
+    feed: &State<AttendanceFeed>,
+    staff: StaffAuth,
+    payload: Json<CheckInPayload>
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Ticket> = db.collection("tickets");
+    let event_id = ObjectId::parse_str(&payload.event_id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+    let gate = payload.gate.trim();
+    if gate.is_empty() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "Gate is required"}))));
+    }
+    let event = find_event(db, event_id).await?;
+
+    let ticket = lookup_scanned_ticket(db, signer, &payload.scan).await.map_err(|e| match e {
+        ScanLookupError::InvalidToken(details) => Custom(Status::BadRequest, Json(json!({
+            "result": "invalid",
+            "error": "Invalid ticket signature",
+            "details": details
+        }))),
+        ScanLookupError::NotFound => Custom(Status::NotFound, Json(json!({"result": "invalid", "error": "Ticket not found"}))),
+        ScanLookupError::Database => Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))),
+    })?;
+    let ticket_id = ticket.id
+        .ok_or_else(|| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+
+    if ticket.event_id != event_id {
+        // Data pemegang tiket event lain tidak dibuka ke petugas event ini
+        return Err(Custom(Status::Conflict, Json(json!({
+            "result": "wrong_event",
+            "error": "Ticket belongs to another event",
+            "event_id": ticket.event_id.to_hex()
+        }))));
+    }
+    // Tiket void tetap boleh scan keluar supaya okupansi tidak tertahan
+    if ticket.status == TicketStatus::Void && payload.direction != ScanDirection::Out {
+        return Err(Custom(Status::Conflict, Json(json!({
+            "result": "void",
+            "error": "Ticket has been voided",
+            "ticket": ticket
+        }))));
+    }
+
+    let now = Utc::now();
+    let code = ticket.code.clone();
+    let outcome = apply_scan(&collection, ticket, &event.entry_rules, payload.direction, gate, &staff.email, now)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update ticket"}))))?;
+
+    let result = outcome_result(&outcome);
+    record_checkin(db, CheckIn {
+        id: None,
+        ticket_id,
+        event_id,
+        code,
+        gate: gate.to_string(),
+        staff_id: staff.email.clone(),
+        scanned_at: now,
+        accepted: !matches!(outcome, ScanOutcome::Rejected { .. }),
+        device_id: None,
+        device_scanned_at: None,
+        direction: payload.direction,
+        undone_at: None,
+        undone_by: None,
+        undo_reason: None,
+    }).await;
+    feed.publish(event_id, ticket_id, gate, result);
+
+    match outcome {
+        ScanOutcome::Rejected { result, ticket } => Err(Custom(Status::Conflict, Json(json!({
+            "result": result,
+            "error": rejection_message(result),
+            "first_scan": first_scan(&ticket),
+            "ticket": ticket
+        })))),
+        ScanOutcome::Admitted(ticket) | ScanOutcome::Exited(ticket) => {
+            let capacity = check_capacity(db, feed, event_id, &event.entry_rules, ticket_id, gate).await;
+            Ok(Json(json!({
+                "result": result,
+                "entry_count": ticket.entry_count,
+                "capacity": capacity,
+                "ticket": ticket
+            })))
+        }
+    }
+}
+
+#[post("/tickets/<id>/undo_checkin", data = "<payload>")]
+pub async fn undo_check_in(
+    db: &State<Database>,
+    feed: &State<AttendanceFeed>,
+    supervisor: SupervisorAuth,
+    id: &str,
+    payload: Json<UndoCheckInPayload>
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Ticket> = db.collection("tickets");
+    let ticket_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid ticket ID"}))))?;
+
+    // Tiket kembali ke kondisi belum pernah masuk, termasuk riwayat masuk ulang
+    let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::Before).build();
+    let previous = collection
+        .find_one_and_update(
+            doc! {"_id": ticket_id, "status": "used"},
+            doc! {
+                "$set": {"status": "valid", "inside": false, "entry_count": 0},
+                "$unset": {"checked_in_at": "", "checked_in_gate": "", "checked_in_by": ""}
+            },
+            options
+        )
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Failed to update ticket"}))))?
+        .ok_or_else(|| Custom(Status::NotFound, Json(json!({"error": "No check-in to undo for this ticket"}))))?;
+
+    let _ = db.collection::<CheckIn>("checkins")
+        .update_many(
+            doc! {"ticket_id": ticket_id, "accepted": true, "undone_at": null},
+            doc! {"$set": {
+                "undone_at": stored_time(Utc::now()),
+                "undone_by": &supervisor.email,
+                "undo_reason": payload.reason.clone(),
+            }},
+            None
+        )
+        .await;
+    feed.publish(previous.event_id, ticket_id, previous.checked_in_gate.as_deref().unwrap_or_default(), "undone");
+
+    let ticket = collection
+        .find_one(doc! {"_id": ticket_id}, None)
+        .await
+        .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+
+    Ok(Json(json!({
+        "message": "Check-in undone",
+        "ticket": ticket
+    })))
+}
+
+fn manifest_status(status: TicketStatus) -> &'static str {
+    match status {
+        TicketStatus::Valid => "v",
+        TicketStatus::Used => "u",
+        TicketStatus::Void => "x",
+    }
+}
+
+#[get("/events/<id>/manifest?<version>")]
+pub async fn get_manifest(
+    db: &State<Database>,
+    _staff: StaffAuth,
+    id: &str,
+    version: Option<&str>
+) -> Result<Json<serde_json::Value>, Status> {
+    let event_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let event = find_event(db, event_id).await.map_err(|e| e.0)?;
+    let tickets = find_tickets(db, doc! {"event_id": event_id})
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut entries: Vec<ManifestTicket> = tickets
+        .iter()
+        .map(|t| ManifestTicket {
+            ticket_id: t.id.map(|id| id.to_hex()).unwrap_or_default(),
+            code: t.code.clone(),
+            tier: t.tier.clone(),
+            status: manifest_status(t.status).to_string(),
+            inside: t.inside,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.code.cmp(&b.code));
+
+    // Versi manifest = hash isi manifest + aturan masuk; berubah setiap ada tiket baru atau status berubah
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&event.entry_rules).unwrap_or_default());
+    for entry in &entries {
+        hasher.update(format!("{}:{}:{}:{}\n", entry.code, entry.tier, entry.status, entry.inside));
+    }
+    let manifest_version: String = hasher.finalize().iter().take(8).map(|b| format!("{:02x}", b)).collect();
+
+    if version == Some(manifest_version.as_str()) {
+        return Err(Status::NotModified);
+    }
+
+    Ok(Json(json!({
+        "event_id": event_id.to_hex(),
+        "version": manifest_version,
+        "generated_at": Utc::now(),
+        "entry_rules": event.entry_rules,
+        "ticket_count": entries.len(),
+        "tickets": entries,
+    })))
+}
+
+#[post("/events/<id>/scans", data = "<payload>")]
+pub async fn sync_scans(
+    db: &State<Database>,
+    signer: &State<TicketSigner>,
+    feed: &State<AttendanceFeed>,
+    staff: StaffAuth,
+    id: &str,
+    payload: Json<ScanBatchPayload>
+) -> Result<Json<serde_json::Value>, Custom<Json<serde_json::Value>>> {
+    let collection: Collection<Ticket> = db.collection("tickets");
+    let checkins: Collection<CheckIn> = db.collection("checkins");
+    let event_id = ObjectId::parse_str(id)
+        .map_err(|_| Custom(Status::BadRequest, Json(json!({"error": "Invalid event ID"}))))?;
+    let device_id = payload.device_id.trim().to_string();
+    if device_id.is_empty() {
+        return Err(Custom(Status::BadRequest, Json(json!({"error": "device_id is required"}))));
+    }
+    let event = find_event(db, event_id).await?;
+    let rules = &event.entry_rules;
+
+    // Proses berurutan menurut waktu scan supaya aturan "scan pertama menang" konsisten
+    let now = Utc::now();
+    let mut scans: Vec<_> = payload.into_inner().scans;
+    scans.sort_by_key(|s| s.scanned_at);
+
+    let mut results = Vec::new();
+    let (mut admitted_count, mut exited_count, mut duplicate_count, mut rejected_count) = (0, 0, 0, 0);
+    let mut last_entry = None;
+
+    for scan in scans {
+        let scanned_at = scan.scanned_at.min(now);
+        let gate = scan.gate.trim().to_string();
+        // Sama dengan `check_in`: gate wajib diisi
+        if gate.is_empty() {
+            rejected_count += 1;
+            results.push(json!({"scan": scan.scan, "result": "invalid", "error": "Gate is required"}));
+            continue;
+        }
+
+        let ticket = match lookup_scanned_ticket(db, signer, &scan.scan).await {
+            Ok(ticket) => ticket,
+            Err(ScanLookupError::Database) => {
+                return Err(Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))));
+            }
+            Err(_) => {
+                rejected_count += 1;
+                results.push(json!({"scan": scan.scan, "result": "invalid"}));
+                continue;
+            }
+        };
+        let Some(ticket_id) = ticket.id else {
+            rejected_count += 1;
+            results.push(json!({"scan": scan.scan, "result": "invalid"}));
+            continue;
+        };
+
+        let voided = ticket.status == TicketStatus::Void && scan.direction != ScanDirection::Out;
+        if ticket.event_id != event_id || voided {
+            rejected_count += 1;
+            let result = if ticket.event_id != event_id { "wrong_event" } else { "void" };
+            results.push(json!({"scan": scan.scan, "result": result, "ticket_id": ticket_id.to_hex()}));
+            continue;
+        }
+
+        // Batch yang dikirim ulang oleh device yang sama tidak diproses dua kali. Kuncinya waktu asli
+        // dari device: nilai yang dibatasi ke `now` berubah di setiap upload jika jam device kecepatan.
+        // Check-in lama tanpa `device_scanned_at` hanya punya `scanned_at`.
+        let device_scanned_at = stored_time(scan.scanned_at);
+        let already_synced = checkins
+            .count_documents(
+                doc! {"ticket_id": ticket_id, "device_id": &device_id, "$or": [
+                    {"device_scanned_at": &device_scanned_at},
+                    {"device_scanned_at": {"$exists": false}, "scanned_at": &device_scanned_at},
+                ]},
+                None
+            )
+            .await
+            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+        if already_synced > 0 {
+            results.push(json!({"scan": scan.scan, "result": "already_synced", "ticket_id": ticket_id.to_hex()}));
+            continue;
+        }
+
+        let code = ticket.code.clone();
+        let mut outcome = apply_scan(&collection, ticket, rules, scan.direction, &gate, &staff.email, scanned_at)
+            .await
+            .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+
+        // Scan masuk offline yang lebih awal dari check-in pertama yang tercatat menjadi check-in
+        // pertama, apa pun aturan re-entry-nya. Jika scan ini ditolak (sekali masuk, sudah di dalam,
+        // batas masuk), scan yang lebih lambat itulah yang kini tercatat sebagai percobaan ganda.
+        let mut superseded = None;
+        let current = match &outcome {
+            ScanOutcome::Admitted(ticket) | ScanOutcome::Exited(ticket) | ScanOutcome::Rejected { ticket, .. } => ticket,
+        };
+        let earlier_than_first = current.checked_in_at.filter(|at| *at > scanned_at);
+        if let (ScanDirection::In, Some(existing_at)) = (scan.direction, earlier_than_first) {
+            let replaced = collection
+                .find_one_and_update(
+                    doc! {"_id": ticket_id, "status": "used", "checked_in_at": stored_time(existing_at)},
+                    doc! {"$set": {
+                        "checked_in_at": stored_time(scanned_at),
+                        "checked_in_gate": &gate,
+                        "checked_in_by": &staff.email,
+                    }},
+                    FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build()
+                )
+                .await
+                .map_err(|_| Custom(Status::InternalServerError, Json(json!({"error": "Database error"}))))?;
+            if let Some(ticket) = replaced {
+                if matches!(outcome, ScanOutcome::Rejected { .. }) {
+                    superseded = Some(existing_at);
+                    let _ = checkins
+                        .update_many(
+                            doc! {"ticket_id": ticket_id, "accepted": true, "undone_at": null, "scanned_at": stored_time(existing_at)},
+                            doc! {"$set": {"accepted": false}},
+                            None
+                        )
+                        .await;
+                }
+                outcome = ScanOutcome::Admitted(ticket);
+            }
+        }
+
+        let result = outcome_result(&outcome);
+        match &outcome {
+            ScanOutcome::Admitted(_) => {
+                admitted_count += 1;
+                last_entry = Some((ticket_id, gate.clone()));
+                results.push(json!({
+                    "scan": scan.scan,
+                    "result": result,
+                    "ticket_id": ticket_id.to_hex(),
+                    "superseded_scan_at": superseded
+                }));
+            }
+            ScanOutcome::Exited(_) => {
+                exited_count += 1;
+                results.push(json!({"scan": scan.scan, "result": result, "ticket_id": ticket_id.to_hex()}));
+            }
+            ScanOutcome::Rejected { result, ticket } => {
+                // Hanya percobaan masuk ganda; penolakan lain (batas masuk, belum di dalam) masuk `rejected`
+                if matches!(*result, "duplicate" | "already_inside") {
+                    duplicate_count += 1;
+                } else {
+                    rejected_count += 1;
+                }
+                results.push(json!({
+                    "scan": scan.scan,
+                    "result": result,
+                    "ticket_id": ticket_id.to_hex(),
+                    "first_scan": first_scan(ticket)
+                }));
+            }
+        }
+
+        feed.publish(event_id, ticket_id, &gate, result);
+        record_checkin(db, CheckIn {
+            id: None,
+            ticket_id,
+            event_id,
+            code,
+            gate,
+            staff_id: staff.email.clone(),
+            scanned_at,
+            accepted: !matches!(outcome, ScanOutcome::Rejected { .. }),
+            device_id: Some(device_id.clone()),
+            device_scanned_at: Some(scan.scanned_at),
+            direction: scan.direction,
+            undone_at: None,
+            undone_by: None,
+            undo_reason: None,
+        }).await;
+    }
+
+    let capacity = match last_entry {
+        Some((ticket_id, gate)) => check_capacity(db, feed, event_id, rules, ticket_id, &gate).await,
+        None => serde_json::Value::Null,
+    };
+
+    Ok(Json(json!({
+        "device_id": device_id,
+        "processed": results.len(),
+        "admitted": admitted_count,
+        "exited": exited_count,
+        "duplicates": duplicate_count,
+        "rejected": rejected_count,
+        "capacity": capacity,
+        "results": results,
+    })))
+}
+
+#[get("/events/<id>/duplicates")]
+pub async fn get_duplicate_scans(
+    db: &State<Database>,
+    _staff: StaffAuth,
+    id: &str
+) -> Result<Json<Vec<CheckIn>>, Status> {
+    let collection: Collection<CheckIn> = db.collection("checkins");
+    let event_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let find_options = FindOptions::builder().sort(doc! {"scanned_at": -1}).build();
+
+    let mut cursor = collection
+        .find(doc! {"event_id": event_id, "accepted": false}, find_options)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut duplicates = Vec::new();
+    while let Some(checkin) = cursor.try_next().await.map_err(|_| Status::InternalServerError)? {
+        duplicates.push(checkin);
+    }
+    Ok(Json(duplicates))
+}
+
+#[get("/events/<id>/attendance")]
+pub async fn get_attendance(
+    db: &State<Database>,
+    _staff: StaffAuth,
+    id: &str
+) -> Result<Json<serde_json::Value>, Status> {
+    let event_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let stats = attendance_stats(db, event_id).await.map_err(|_| Status::InternalServerError)?;
+    Ok(Json(json!(stats)))
+}
+
+#[get("/events/<id>/attendance/arrivals?<bucket>&<since>")]
+pub async fn get_arrivals(
+    db: &State<Database>,
+    _staff: StaffAuth,
+    id: &str,
+    bucket: Option<i64>,
+    since: Option<&str>
+) -> Result<Json<serde_json::Value>, Status> {
+    let event_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let since = since
+        .map(|s| DateTime::parse_from_rfc3339(s).map(|d| d.with_timezone(&Utc)))
+        .transpose()
+        .map_err(|_| Status::BadRequest)?;
+    let bucket_minutes = bucket.unwrap_or(5).clamp(1, 24 * 60);
+
+    let series = arrival_series(db, event_id, bucket_minutes, since)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    Ok(Json(json!({
+        "event_id": event_id.to_hex(),
+        "bucket_minutes": bucket_minutes,
+        "series": series,
+    })))
+}
+
+#[get("/events/<id>/no_shows")]
+pub async fn get_no_shows(
+    db: &State<Database>,
+    _staff: StaffAuth,
+    id: &str
+) -> Result<Json<Vec<Ticket>>, Status> {
+    let event_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let tickets = find_no_shows(db, event_id).await.map_err(|_| Status::InternalServerError)?;
+    Ok(Json(tickets))
+}
+
+/// Stream SSE untuk dashboard: snapshot `stats` saat terhubung, lalu `scan`
+/// + `stats` terbaru setiap ada hasil scan untuk event ini.
+#[get("/events/<id>/attendance/live")]
+pub async fn attendance_live(
+    db: &State<Database>,
+    feed: &State<AttendanceFeed>,
+    _staff: StaffAuth,
+    id: &str,
+    mut shutdown: Shutdown
+) -> Result<EventStream![], Status> {
+    let event_id = ObjectId::parse_str(id).map_err(|_| Status::BadRequest)?;
+    let db = db.inner().clone();
+    let mut updates = feed.subscribe();
+    let mut stats = feed.subscribe_stats();
+    let initial = attendance_stats(&db, event_id).await.map_err(|_| Status::InternalServerError)?;
+
+    Ok(EventStream! {
+        yield SseEvent::json(&initial).event("stats");
+        loop {
+            let message = select! {
+                update = updates.recv() => match update {
+                    Ok(update) => LiveMessage::Scan(update),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                latest = stats.recv() => match latest {
+                    Ok(latest) => LiveMessage::Stats(latest),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+                _ = &mut shutdown => break,
+            };
+            match message {
+                LiveMessage::Scan(update) if update.event_id == event_id.to_hex() => {
+                    let name = if update.result == "capacity_alert" { "capacity" } else { "scan" };
+                    yield SseEvent::json(&update).event(name);
+                }
+                LiveMessage::Stats(latest) if latest.event_id == event_id.to_hex() => {
+                    yield SseEvent::json(&latest).event("stats");
+                }
+                _ => {}
+            }
+        }
+    })
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![
+        check_in,
+        undo_check_in,
+        get_manifest,
+        sync_scans,
+        get_duplicate_scans,
+        get_attendance,
+        get_arrivals,
+        get_no_shows,
+        attendance_live
+    ]
+}
@@ -0,0 +1,49 @@
+use mongodb::{bson::{doc, oid::ObjectId}, Collection, Database};
+
+use crate::models::access_code::AccessCode;
+
+pub async fn find_usable_access_code(
+    db: &Database,
+    event_id: &ObjectId,
+    code: &str,
+) -> Result<Option<AccessCode>, mongodb::error::Error> {
+    let collection: Collection<AccessCode> = db.collection("access_codes");
+    let access_code = collection
+        .find_one(doc! {"event_id": event_id, "code": AccessCode::normalize(code)}, None)
+        .await?;
+
+    Ok(access_code.filter(|c| c.is_usable(chrono::Utc::now())))
+}
+
+/// Tambah `used_count` secara atomik; gagal jika kuota sudah habis.
+pub async fn redeem_access_code(db: &Database, id: &ObjectId) -> Result<bool, mongodb::error::Error> {
+    let collection: Collection<AccessCode> = db.collection("access_codes");
+    let result = collection
+        .update_one(
+            doc! {
+                "_id": id,
+                "$or": [
+                    {"max_uses": null},
+                    {"$expr": {"$lt": ["$used_count", "$max_uses"]}}
+                ]
+            },
+            doc! {"$inc": {"used_count": 1}},
+            None,
+        )
+        .await?;
+    Ok(result.modified_count > 0)
+}
+
+pub async fn release_access_code(db: &Database, id: &ObjectId) {
+    let collection: Collection<AccessCode> = db.collection("access_codes");
+    if let Err(e) = collection
+        .update_one(
+            doc! {"_id": id, "used_count": {"$gt": 0}},
+            doc! {"$inc": {"used_count": -1}},
+            None,
+        )
+        .await
+    {
+        eprintln!("Failed to release access code {}: {:?}", id, e);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::Utc;
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId}, options::FindOptions, Collection, Database};
+
+use crate::config::Config;
+use crate::models::announcement::{Announcement, AnnouncementProgress, CreateAnnouncementPayload};
+use crate::models::email_outbox::OutboxEmail;
+use crate::models::email_template::EmailTemplateKind;
+use crate::models::{event::Event, order::{Order, OrderStatus}, ticket::{Ticket, TicketStatus}};
+use crate::utils::email_templates::{email_context, find_effective_template, render_email};
+use crate::utils::mailer::EmailMessage;
+use crate::utils::outbox::{EmailOutbox, OutboxSource};
+use crate::utils::suppressions::normalize_email;
+use crate::utils::tickets::find_tickets;
+use crate::utils::wallet::WalletPasses;
+
+/// Order penerima pengumuman: satu order (yang paling awal) per alamat email.
+async fn find_recipients(db: &Database, event_id: ObjectId, tiers: &[String], statuses: &[OrderStatus]) -> mongodb::error::Result<Vec<Order>> {
+    let statuses: Vec<String> = if statuses.is_empty() {
+        vec![OrderStatus::Paid.to_string(), OrderStatus::Sent.to_string()]
+    } else {
+        statuses.iter().map(|s| s.to_string()).collect()
+    };
+    let mut filter = doc! {"event_id": event_id, "status": {"$in": statuses}};
+    if !tiers.is_empty() {
+        filter.insert("tier", doc! {"$in": tiers});
+    }
+
+    let find_options = FindOptions::builder().sort(doc! {"created_at": 1}).build();
+    let orders: Vec<Order> = db
+        .collection::<Order>("orders")
+        .find(filter, find_options)
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut seen = HashSet::new();
+    Ok(orders
+        .into_iter()
+        .filter(|order| seen.insert(normalize_email(&order.buyer_email)))
+        .collect())
+}
+
+/// Render dan antrekan satu email per penerima lewat outbox, lalu simpan riwayat pengumuman.
+/// Mengembalikan `None` jika tidak ada order yang cocok dengan filter.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_announcement(
+    db: &Database,
+    config: &Config,
+    wallet: &WalletPasses,
+    outbox: &EmailOutbox,
+    event: &Event,
+    payload: CreateAnnouncementPayload,
+    created_by: &str,
+) -> Result<Option<Announcement>> {
+    let event_id = event.id.ok_or_else(|| anyhow::anyhow!("Event has no id"))?;
+    let recipients = find_recipients(db, event_id, &payload.tiers, &payload.order_statuses).await?;
+    let Some(first) = recipients.first() else {
+        return Ok(None);
+    };
+
+    let template = find_effective_template(db, EmailTemplateKind::Announcement, Some(event_id)).await?;
+    let subject_override = payload.subject.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    // Render penerima pertama dulu supaya template yang rusak gagal sebelum ada email yang antre
+    let preview = render_email(
+        &template.subject,
+        &template.html,
+        &template.text,
+        &email_context(config, wallet, event, first, &[], &payload.message, None),
+    )?;
+
+    let mut announcement = Announcement {
+        id: None,
+        event_id,
+        subject: subject_override.map(str::to_string).unwrap_or(preview.subject),
+        message: payload.message,
+        tiers: payload.tiers,
+        order_statuses: payload.order_statuses,
+        recipient_count: recipients.len() as u32,
+        created_by: created_by.to_string(),
+        created_at: Utc::now(),
+    };
+    let collection: Collection<Announcement> = db.collection("announcements");
+    let result = collection.insert_one(&announcement, None).await?;
+    announcement.id = result.inserted_id.as_object_id();
+
+    for order in &recipients {
+        let tickets = find_tickets(db, doc! {"order_id": order.id}).await?;
+        let active_tickets: Vec<&Ticket> = tickets.iter().filter(|t| t.status != TicketStatus::Void).collect();
+        let context = email_context(config, wallet, event, order, &active_tickets, &announcement.message, None);
+        let rendered = match render_email(&template.subject, &template.html, &template.text, &context) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                println!("❌ Failed to render announcement for order {}: {:?}", order.midtrans_order_id, e);
+                continue;
+            }
+        };
+
+        let message = EmailMessage {
+            to: order.buyer_email.clone(),
+            subject: subject_override.map(str::to_string).unwrap_or(rendered.subject),
+            text: rendered.text,
+            html: Some(rendered.html),
+            attachments: Vec::new(),
+        };
+        let source = OutboxSource { order_id: order.id, event_id: Some(event_id), announcement_id: announcement.id };
+        outbox.enqueue(EmailTemplateKind::Announcement.as_str(), source, message).await?;
+    }
+    Ok(Some(announcement))
+}
+
+pub async fn find_announcements(db: &Database, event_id: ObjectId) -> mongodb::error::Result<Vec<Announcement>> {
+    let find_options = FindOptions::builder().sort(doc! {"created_at": -1}).build();
+    db.collection::<Announcement>("announcements")
+        .find(doc! {"event_id": event_id}, find_options)
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Hitung email pengumuman per status langsung dari outbox.
+pub async fn announcement_progress(db: &Database, announcement_id: ObjectId) -> mongodb::error::Result<AnnouncementProgress> {
+    let pipeline = vec![
+        doc! { "$match": { "announcement_id": announcement_id } },
+        doc! { "$group": { "_id": "$status", "count": { "$sum": 1 } } },
+    ];
+    let mut cursor = db
+        .collection::<OutboxEmail>("email_outbox")
+        .aggregate(pipeline, None)
+        .await?;
+
+    let mut progress = AnnouncementProgress::default();
+    while let Some(document) = cursor.try_next().await? {
+        let count = document.get_i32("count").unwrap_or_default() as u32;
+        match document.get_str("_id").unwrap_or_default() {
+            "pending" => progress.pending = count,
+            "sending" => progress.sending = count,
+            "sent" => progress.sent = count,
+            "dead" => progress.dead = count,
+            "suppressed" => progress.suppressed = count,
+            _ => {}
+        }
+    }
+    Ok(progress)
+}
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId, Bson, Document}, options::FindOptions, Collection, Database};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::{pin, select, sync::broadcast::{self, error::RecvError}, time::sleep};
+use rocket::{Orbit, Rocket, Shutdown};
+
+use crate::models::checkin::{ArrivalBucket, AttendanceStats, AttendanceUpdate, CheckIn, GateCount, ScanDirection, TierAttendance};
+use crate::models::event::Event;
+use crate::models::ticket::Ticket;
+use crate::utils::tickets::find_tickets;
+
+const FEED_CAPACITY: usize = 256;
+/// Scan yang datang berdekatan digabung jadi satu perhitungan statistik
+const STATS_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Saluran broadcast hasil scan dan statistik untuk endpoint SSE. Statistik dihitung sekali
+/// oleh `AttendanceStatsWorker` lalu dibagikan ke semua dashboard, bukan per subscriber.
+#[derive(Clone)]
+pub struct AttendanceFeed {
+    sender: broadcast::Sender<AttendanceUpdate>,
+    stats: broadcast::Sender<AttendanceStats>,
+}
+
+impl AttendanceFeed {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(FEED_CAPACITY);
+        let (stats, _) = broadcast::channel(FEED_CAPACITY);
+        AttendanceFeed { sender, stats }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AttendanceUpdate> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscribe_stats(&self) -> broadcast::Receiver<AttendanceStats> {
+        self.stats.subscribe()
+    }
+
+    pub fn publish(&self, event_id: ObjectId, ticket_id: ObjectId, gate: &str, result: &str) {
+        // Error hanya berarti belum ada dashboard yang mendengarkan
+        let _ = self.sender.send(AttendanceUpdate {
+            event_id: event_id.to_hex(),
+            ticket_id: ticket_id.to_hex(),
+            gate: gate.to_string(),
+            result: result.to_string(),
+            at: Utc::now(),
+        });
+    }
+}
+
+impl Default for AttendanceFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn find_checkins(db: &Database, event_id: ObjectId) -> mongodb::error::Result<Vec<CheckIn>> {
+    let collection: Collection<CheckIn> = db.collection("checkins");
+    let find_options = FindOptions::builder().sort(doc! {"scanned_at": 1}).build();
+    let mut cursor = collection.find(doc! {"event_id": event_id}, find_options).await?;
+
+    let mut checkins = Vec::new();
+    while let Some(checkin) = cursor.try_next().await? {
+        checkins.push(checkin);
+    }
+    Ok(checkins)
+}
+
+fn is_admission(checkin: &CheckIn) -> bool {
+    checkin.accepted && checkin.undone_at.is_none() && checkin.direction == ScanDirection::In
+}
+
+/// Jumlah pengunjung yang saat ini berada di dalam venue.
+pub async fn current_occupancy(db: &Database, event_id: ObjectId) -> mongodb::error::Result<i64> {
+    let count = db
+        .collection::<Ticket>("tickets")
+        .count_documents(doc! {"event_id": event_id, "inside": true, "status": {"$ne": "void"}}, None)
+        .await?;
+    Ok(count as i64)
+}
+
+// `$sum` bisa menghasilkan Int32 atau Int64 tergantung besarnya angka
+fn group_count(document: &Document, key: &str) -> i64 {
+    match document.get(key) {
+        Some(Bson::Int32(n)) => *n as i64,
+        Some(Bson::Int64(n)) => *n,
+        _ => 0,
+    }
+}
+
+async fn aggregate(
+    db: &Database,
+    collection: &str,
+    pipeline: Vec<Document>,
+) -> mongodb::error::Result<Vec<Document>> {
+    db.collection::<Document>(collection)
+        .aggregate(pipeline, None)
+        .await?
+        .try_collect()
+        .await
+}
+
+pub async fn attendance_stats(db: &Database, event_id: ObjectId) -> mongodb::error::Result<AttendanceStats> {
+    let ticket_groups = aggregate(db, "tickets", vec![
+        doc! {"$match": {"event_id": event_id}},
+        doc! {"$group": {
+            "_id": {"tier": "$tier", "status": "$status"},
+            "count": {"$sum": 1},
+            // Tiket void lama bisa masih bertanda `inside`; tidak ikut okupansi
+            "inside": {"$sum": {"$cond": [{"$and": ["$inside", {"$ne": ["$status", "void"]}]}, 1, 0]}},
+        }},
+    ]).await?;
+    // Sama dengan `is_admission`: `accepted` dan `direction` lama bisa kosong (default true / in)
+    let accepted = doc! {"$ne": ["$accepted", false]};
+    let exit = doc! {"$eq": ["$direction", "out"]};
+    let checkin_groups = aggregate(db, "checkins", vec![
+        doc! {"$match": {"event_id": event_id}},
+        doc! {"$group": {
+            "_id": "$gate",
+            "checked_in": {"$sum": {"$cond": [{"$and": [
+                &accepted,
+                {"$eq": [{"$ifNull": ["$undone_at", null]}, null]},
+                {"$not": [&exit]},
+            ]}, 1, 0]}},
+            "exits": {"$sum": {"$cond": [{"$and": [&accepted, &exit]}, 1, 0]}},
+            "duplicates": {"$sum": {"$cond": [{"$eq": ["$accepted", false]}, 1, 0]}},
+            "last_scan_at": {"$max": "$scanned_at"},
+        }},
+    ]).await?;
+    let venue_capacity = db
+        .collection::<Event>("events")
+        .find_one(doc! {"_id": event_id}, None)
+        .await?
+        .and_then(|event| event.entry_rules.venue_capacity);
+
+    let mut by_tier: BTreeMap<String, TierAttendance> = BTreeMap::new();
+    let (mut checked_in, mut no_show, mut void, mut occupancy) = (0, 0, 0, 0);
+    for group in &ticket_groups {
+        let key = group.get_document("_id").ok();
+        let tier_name = key.and_then(|k| k.get_str("tier").ok()).unwrap_or_default().to_string();
+        let status = key.and_then(|k| k.get_str("status").ok()).unwrap_or_default();
+        let count = group_count(group, "count");
+        occupancy += group_count(group, "inside");
+        if status == "void" {
+            void += count;
+            continue;
+        }
+        let tier = by_tier.entry(tier_name.clone()).or_insert_with(|| TierAttendance {
+            tier: tier_name,
+            tickets: 0,
+            checked_in: 0,
+        });
+        tier.tickets += count;
+        if status == "used" {
+            tier.checked_in += count;
+            checked_in += count;
+        } else {
+            no_show += count;
+        }
+    }
+
+    let mut by_gate: BTreeMap<String, GateCount> = BTreeMap::new();
+    let mut duplicates = 0;
+    let mut last_scan_at: Option<DateTime<Utc>> = None;
+    for group in &checkin_groups {
+        let gate = group.get_str("_id").unwrap_or_default().to_string();
+        let count = GateCount {
+            gate: gate.clone(),
+            checked_in: group_count(group, "checked_in"),
+            exits: group_count(group, "exits"),
+            duplicates: group_count(group, "duplicates"),
+        };
+        duplicates += count.duplicates;
+        let scanned_at = group
+            .get_str("last_scan_at")
+            .ok()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Utc));
+        last_scan_at = last_scan_at.max(scanned_at);
+        by_gate.insert(gate, count);
+    }
+
+    Ok(AttendanceStats {
+        event_id: event_id.to_hex(),
+        total_tickets: checked_in + no_show,
+        checked_in,
+        no_show,
+        void,
+        duplicates,
+        occupancy,
+        venue_capacity,
+        by_gate: by_gate.into_values().collect(),
+        by_tier: by_tier.into_values().collect(),
+        last_scan_at,
+        generated_at: Utc::now(),
+    })
+}
+
+/// Jumlah kedatangan per gate dalam interval `bucket_minutes`, berurutan menurut waktu.
+pub async fn arrival_series(
+    db: &Database,
+    event_id: ObjectId,
+    bucket_minutes: i64,
+    since: Option<DateTime<Utc>>,
+) -> mongodb::error::Result<Vec<ArrivalBucket>> {
+    let bucket = Duration::minutes(bucket_minutes.max(1));
+    let checkins = find_checkins(db, event_id).await?;
+
+    let mut counts: BTreeMap<(DateTime<Utc>, String), i64> = BTreeMap::new();
+    for checkin in checkins.iter().filter(|c| is_admission(c)) {
+        if since.is_some_and(|since| checkin.scanned_at < since) {
+            continue;
+        }
+        let starts_at = checkin.scanned_at.duration_trunc(bucket).unwrap_or(checkin.scanned_at);
+        *counts.entry((starts_at, checkin.gate.clone())).or_insert(0) += 1;
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(|((starts_at, gate), count)| ArrivalBucket { starts_at, gate, count })
+        .collect())
+}
+
+/// Tiket yang belum check-in (tidak termasuk tiket void).
+pub async fn find_no_shows(db: &Database, event_id: ObjectId) -> mongodb::error::Result<Vec<Ticket>> {
+    find_tickets(db, doc! {"event_id": event_id, "status": "valid"}).await
+}
+
+async fn run(db: Database, feed: AttendanceFeed, mut shutdown: Shutdown) {
+    let mut updates = feed.subscribe();
+    loop {
+        let first = select! {
+            update = updates.recv() => match update {
+                Ok(update) => update,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            _ = &mut shutdown => break,
+        };
+
+        // Kumpulkan event lain yang ikut di-scan selama jeda debounce
+        let mut dirty = HashSet::from([first.event_id]);
+        let debounce = sleep(STATS_DEBOUNCE);
+        pin!(debounce);
+        loop {
+            select! {
+                _ = &mut debounce => break,
+                update = updates.recv() => match update {
+                    Ok(update) => { dirty.insert(update.event_id); }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                },
+            }
+        }
+
+        if feed.stats.receiver_count() == 0 {
+            continue;
+        }
+        for event_id in dirty.iter().filter_map(|id| ObjectId::parse_str(id).ok()) {
+            match attendance_stats(&db, event_id).await {
+                Ok(stats) => { let _ = feed.stats.send(stats); }
+                Err(e) => println!("❌ Attendance stats error for event {}: {:?}", event_id, e),
+            }
+        }
+    }
+}
+
+/// Menghitung ulang statistik kehadiran setelah scan dan membagikannya ke dashboard live.
+pub struct AttendanceStatsWorker;
+
+#[rocket::async_trait]
+impl Fairing for AttendanceStatsWorker {
+    fn info(&self) -> Info {
+        Info {
+            name: "Attendance stats worker",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let (Some(db), Some(feed)) = (rocket.state::<Database>(), rocket.state::<AttendanceFeed>()) else {
+            return;
+        };
+        rocket::tokio::spawn(run(db.clone(), feed.clone(), rocket.shutdown()));
+    }
+}
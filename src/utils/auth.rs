@@ -4,14 +4,17 @@ use bcrypt::{hash, verify, DEFAULT_COST};
 use rocket::http::Status;
 use rocket::request::{Request, FromRequest, Outcome};
 use crate::config::Config;
+use crate::models::admin::AdminRole;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
+    // Token lama tanpa role dianggap milik admin
+    #[serde(default)]
+    pub role: AdminRole,
     exp: usize,
 }
 
-#[allow(dead_code)]
 pub fn hash_password(password: &str) -> Result<String, bcrypt::BcryptError> {
     hash(password, DEFAULT_COST)
 }
@@ -20,7 +23,7 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
     verify(password, hash).unwrap_or(false)
 }
 
-pub fn create_jwt(email: &str, secret: &str) -> Result<String, jsonwebtoken::errors::Error> {
+pub fn create_jwt(email: &str, role: AdminRole, secret: &str) -> Result<String, jsonwebtoken::errors::Error> {
     let expiration = chrono::Utc::now()
         .checked_add_signed(chrono::Duration::hours(24))
         .expect("valid timestamp")
@@ -28,6 +31,7 @@ pub fn create_jwt(email: &str, secret: &str) -> Result<String, jsonwebtoken::err
 
     let claims = Claims {
         sub: email.to_owned(),
+        role,
         exp: expiration,
     };
 
@@ -38,6 +42,26 @@ pub fn create_jwt(email: &str, secret: &str) -> Result<String, jsonwebtoken::err
     )
 }
 
+fn decode_claims(request: &Request<'_>) -> Result<Claims, Status> {
+    // Ambil JWT secret dari state Config
+    let config = request.rocket().state::<Config>().ok_or(Status::InternalServerError)?;
+
+    let token = request
+        .headers()
+        .get_one("Authorization")
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .ok_or(Status::Unauthorized)?;
+
+    let validation = Validation::new(jsonwebtoken::Algorithm::HS256);
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_ref()),
+        &validation,
+    )
+    .map(|token_data| token_data.claims)
+    .map_err(|_| Status::Unauthorized)
+}
+
 #[derive(Debug, Clone)]
 pub struct AdminAuth {
     pub email: String,
@@ -48,27 +72,46 @@ impl<'r> FromRequest<'r> for AdminAuth {
     type Error = ();
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        // Ambil JWT secret dari state Config
-        let config = match request.rocket().state::<Config>() {
-            Some(c) => c,
-            None => return Outcome::Error((Status::InternalServerError, ())),
-        };
-
-        if let Some(auth_header) = request.headers().get_one("Authorization") {
-            if let Some(token) = auth_header.strip_prefix("Bearer ") {
-                let validation = Validation::new(jsonwebtoken::Algorithm::HS256);
-                if let Ok(token_data) = decode::<Claims>(
-                    token,
-                    &DecodingKey::from_secret(config.jwt_secret.as_ref()),
-                    &validation,
-                ) {
-                    return Outcome::Success(AdminAuth {
-                        email: token_data.claims.sub,
-                    });
-                }
-            }
+        match decode_claims(request) {
+            Ok(claims) if claims.role == AdminRole::Admin => Outcome::Success(AdminAuth { email: claims.sub }),
+            Ok(_) => Outcome::Error((Status::Forbidden, ())),
+            Err(status) => Outcome::Error((status, ())),
         }
+    }
+}
 
-        Outcome::Error((Status::Unauthorized, ()))
+/// Akun petugas pintu (staff, supervisor, maupun admin).
+#[derive(Debug, Clone)]
+pub struct StaffAuth {
+    pub email: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for StaffAuth {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match decode_claims(request) {
+            Ok(claims) => Outcome::Success(StaffAuth { email: claims.sub }),
+            Err(status) => Outcome::Error((status, ())),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SupervisorAuth {
+    pub email: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SupervisorAuth {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match decode_claims(request) {
+            Ok(claims) if claims.role != AdminRole::Staff => Outcome::Success(SupervisorAuth { email: claims.sub }),
+            Ok(_) => Outcome::Error((Status::Forbidden, ())),
+            Err(status) => Outcome::Error((status, ())),
+        }
     }
-}
\ No newline at end of file
+}
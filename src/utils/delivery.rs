@@ -0,0 +1,178 @@
+use anyhow::Result;
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId}, options::{Collation, CollationStrength, FindOptions}, Database};
+
+use crate::config::Config;
+use crate::models::{event::Event, order::Order, ticket::{Ticket, TicketStatus}};
+use crate::models::email_template::EmailTemplateKind;
+use crate::utils::email_templates::{email_context, find_effective_template, render_email};
+use crate::utils::ical::event_invite;
+use crate::utils::mailer::{EmailAttachment, EmailMessage};
+use crate::utils::notifier::{tickets_ready_text, Notifier};
+use crate::utils::outbox::{EmailOutbox, OutboxSource};
+use crate::utils::pdf::{render_ticket_pdf, TicketPage};
+use crate::utils::signing::TicketSigner;
+use crate::utils::tickets::{find_tickets, issue_tickets_for_order, ticket_qr_payload};
+use crate::utils::wallet::WalletPasses;
+
+pub const AUTO_DELIVERY_MESSAGE: &str = "Pembayaran Anda telah kami terima. Tiket Anda terlampir dalam email ini.";
+pub const BUYER_RESEND_MESSAGE: &str = "Berikut tiket Anda yang diminta untuk dikirim ulang.";
+
+fn invite_attachment(config: &Config, event: &Event) -> EmailAttachment {
+    EmailAttachment {
+        filename: "event.ics".to_string(),
+        content_type: "text/calendar; charset=utf-8".to_string(),
+        content: event_invite(config, event).into_bytes(),
+    }
+}
+
+/// Antrekan email tiket (PDF, undangan kalender, QR & link wallet) untuk order yang sudah dibayar;
+/// order ditandai `sent` oleh worker outbox setelah email terkirim. Dipakai webhook pembayaran
+/// dan aksi kirim ulang admin. Isi email dari template `ticket_delivery`; `subject` yang diisi
+/// menggantikan subject template. Pembeli yang memilih SMS/WhatsApp juga menerima link tiketnya
+/// lewat `notifier`. Mengembalikan id outbox.
+#[allow(clippy::too_many_arguments)]
+pub async fn deliver_order_tickets(
+    db: &Database,
+    config: &Config,
+    signer: &TicketSigner,
+    wallet: &WalletPasses,
+    outbox: &EmailOutbox,
+    notifier: &Notifier,
+    order: &Order,
+    subject: Option<&str>,
+    message: &str,
+) -> Result<ObjectId> {
+    let order_id = order.id.ok_or_else(|| anyhow::anyhow!("Order has no id"))?;
+    let tickets = issue_tickets_for_order(db, order).await?;
+    let active_tickets: Vec<&Ticket> = tickets
+        .iter()
+        .filter(|t| t.status != TicketStatus::Void)
+        .collect();
+
+    let event_id = order.event_id;
+    let event = db
+        .collection::<Event>("events")
+        .find_one(doc! {"_id": event_id}, None)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Event not found"))?;
+
+    let pdf_pages: Vec<TicketPage> = active_tickets
+        .iter()
+        .map(|t| TicketPage { ticket: t, qr_payload: ticket_qr_payload(signer, t) })
+        .collect();
+    let ticket_pdf = render_ticket_pdf(&event, order, &pdf_pages)?;
+
+    let template = find_effective_template(db, EmailTemplateKind::TicketDelivery, Some(event_id)).await?;
+    let context = email_context(config, wallet, &event, order, &active_tickets, message, None);
+    let rendered = render_email(&template.subject, &template.html, &template.text, &context)?;
+
+    let email = EmailMessage {
+        to: order.buyer_email.clone(),
+        subject: subject.filter(|s| !s.trim().is_empty()).map(str::to_string).unwrap_or(rendered.subject),
+        text: rendered.text,
+        html: Some(rendered.html),
+        attachments: vec![
+            EmailAttachment {
+                filename: format!("tiket-{}.pdf", order.midtrans_order_id),
+                content_type: "application/pdf".to_string(),
+                content: ticket_pdf,
+            },
+            invite_attachment(config, &event),
+        ],
+    };
+    let outbox_id = outbox
+        .enqueue(
+            EmailTemplateKind::TicketDelivery.as_str(),
+            OutboxSource { order_id: Some(order_id), event_id: Some(event_id), announcement_id: None },
+            email,
+        )
+        .await?;
+    notifier.notify(order, tickets_ready_text(config, &event, order, &active_tickets));
+    Ok(outbox_id)
+}
+
+/// Antrekan email transaksional order tanpa lampiran tiket: konfirmasi pesanan, pembatalan,
+/// atau refund, dari template `kind`. Konfirmasi pesanan ikut membawa undangan kalender.
+/// Mengembalikan id outbox.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_order_email(
+    db: &Database,
+    config: &Config,
+    wallet: &WalletPasses,
+    outbox: &EmailOutbox,
+    order: &Order,
+    kind: EmailTemplateKind,
+    message: &str,
+    payment_url: Option<&str>,
+) -> Result<ObjectId> {
+    let order_id = order.id.ok_or_else(|| anyhow::anyhow!("Order has no id"))?;
+    let event_id = order.event_id;
+    let event = db
+        .collection::<Event>("events")
+        .find_one(doc! {"_id": event_id}, None)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Event not found"))?;
+
+    let template = find_effective_template(db, kind, Some(event_id)).await?;
+    let context = email_context(config, wallet, &event, order, &[], message, payment_url);
+    let rendered = render_email(&template.subject, &template.html, &template.text, &context)?;
+    let email = EmailMessage {
+        to: order.buyer_email.clone(),
+        subject: rendered.subject,
+        text: rendered.text,
+        html: Some(rendered.html),
+        attachments: if kind == EmailTemplateKind::OrderConfirmation {
+            vec![invite_attachment(config, &event)]
+        } else {
+            Vec::new()
+        },
+    };
+    let outbox_id = outbox
+        .enqueue(
+            kind.as_str(),
+            OutboxSource { order_id: Some(order_id), event_id: Some(event_id), announcement_id: None },
+            email,
+        )
+        .await?;
+    Ok(outbox_id)
+}
+
+/// Kirim ulang tiket yang masih berlaku ke pembeli (satu email per order yang sudah pernah
+/// dikirimi tiket), opsional dibatasi ke satu nomor order. Mengembalikan jumlah email yang diantrekan.
+#[allow(clippy::too_many_arguments)]
+pub async fn resend_buyer_tickets(
+    db: &Database,
+    config: &Config,
+    signer: &TicketSigner,
+    wallet: &WalletPasses,
+    outbox: &EmailOutbox,
+    notifier: &Notifier,
+    email: &str,
+    order_number: Option<&str>,
+) -> Result<usize> {
+    let mut filter = doc! {"buyer_email": email.trim(), "status": "sent"};
+    if let Some(order_number) = order_number {
+        filter.insert("midtrans_order_id", order_number.trim());
+    }
+    // Alamat email dicocokkan tanpa membedakan huruf besar/kecil
+    let collation = Collation::builder().locale("en").strength(CollationStrength::Secondary).build();
+    let find_options = FindOptions::builder().collation(collation).build();
+    let orders: Vec<Order> = db
+        .collection::<Order>("orders")
+        .find(filter, find_options)
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut queued = 0;
+    for order in &orders {
+        let valid_tickets = find_tickets(db, doc! {"order_id": order.id, "status": "valid"}).await?;
+        if valid_tickets.is_empty() {
+            continue;
+        }
+        deliver_order_tickets(db, config, signer, wallet, outbox, notifier, order, None, BUYER_RESEND_MESSAGE).await?;
+        queued += 1;
+    }
+    Ok(queued)
+}
@@ -0,0 +1,327 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use futures::TryStreamExt;
+use minijinja::{Environment, UndefinedBehavior};
+use mongodb::{bson::{doc, oid::ObjectId}, Collection, Database};
+use serde_json::json;
+
+use crate::config::Config;
+use crate::models::email_template::{EffectiveEmailTemplate, EmailTemplate, EmailTemplateKind, RenderedEmail, TemplateSource};
+use crate::models::{event::{Event, DEFAULT_TIMEZONE}, order::Order, ticket::Ticket};
+use crate::utils::tickets::ticket_qr_url;
+use crate::utils::wallet::{wallet_links, WalletPasses};
+
+const ORDER_CONFIRMATION_SUBJECT: &str = "Pesanan {{ order.number }} untuk {{ event.name }}";
+const ORDER_CONFIRMATION_TEXT: &str = "Halo {{ order.buyer_name }},
+
+Pesanan Anda untuk {{ event.name }} telah kami terima.
+
+Nomor pesanan: {{ order.number }}
+Tiket: {{ order.quantity }} x {{ order.tier }}
+Total: {{ order.total }}
+{% if payment_url %}
+Selesaikan pembayaran di: {{ payment_url }}
+{% endif %}
+{{ event.name }}
+{{ event.date }} ({{ event.timezone }})
+{{ event.location }}";
+const ORDER_CONFIRMATION_HTML: &str = "<p>Halo {{ order.buyer_name }},</p>
+<p>Pesanan Anda untuk <strong>{{ event.name }}</strong> telah kami terima.</p>
+<p>Nomor pesanan: {{ order.number }}<br>Tiket: {{ order.quantity }} x {{ order.tier }}<br>Total: {{ order.total }}</p>
+{% if payment_url %}<p><a href=\"{{ payment_url }}\">Selesaikan pembayaran</a></p>{% endif %}
+<p>{{ event.date }} ({{ event.timezone }})<br>{{ event.location }}</p>";
+
+const TICKET_DELIVERY_SUBJECT: &str = "Tiket Anda untuk {{ event.name }}";
+const TICKET_DELIVERY_TEXT: &str = "Halo {{ order.buyer_name }},
+
+{{ message }}
+
+Kode tiket Anda:
+{% for ticket in tickets %}- {{ ticket.code }} ({{ ticket.tier }}): {{ ticket.qr_url }}
+{% if ticket.apple_wallet_url %}  Apple Wallet: {{ ticket.apple_wallet_url }}
+{% endif %}{% if ticket.google_wallet_url %}  Google Wallet: {{ ticket.google_wallet_url }}
+{% endif %}{% endfor %}
+Terima kasih atas pembelian tiket Anda.";
+const TICKET_DELIVERY_HTML: &str = "<p>Halo {{ order.buyer_name }},</p>
+<p style=\"white-space: pre-line\">{{ message }}</p>
+<p>Kode tiket Anda:</p>
+{% for ticket in tickets %}<p><strong>{{ ticket.code }}</strong> ({{ ticket.tier }})<br><img src=\"{{ ticket.qr_url }}\" alt=\"QR {{ ticket.code }}\" width=\"240\" height=\"240\"><br>
+{% if ticket.apple_wallet_url %}<a href=\"{{ ticket.apple_wallet_url }}\">Tambahkan ke Apple Wallet</a> {% endif %}
+{% if ticket.google_wallet_url %}<a href=\"{{ ticket.google_wallet_url }}\">Simpan ke Google Wallet</a>{% endif %}</p>
+{% endfor %}<p>Terima kasih atas pembelian tiket Anda.</p>";
+
+const PAYMENT_REMINDER_SUBJECT: &str = "Selesaikan pembayaran tiket {{ event.name }}";
+const PAYMENT_REMINDER_TEXT: &str = "Halo {{ order.buyer_name }},
+
+Pesanan {{ order.number }} ({{ order.quantity }} x {{ order.tier }}, {{ order.total }}) belum dibayar.
+{% if payment_url %}
+Lanjutkan pembayaran di: {{ payment_url }}
+{% endif %}
+Abaikan email ini jika Anda sudah menyelesaikan pembayaran.";
+const PAYMENT_REMINDER_HTML: &str = "<p>Halo {{ order.buyer_name }},</p>
+<p>Pesanan {{ order.number }} ({{ order.quantity }} x {{ order.tier }}, {{ order.total }}) untuk <strong>{{ event.name }}</strong> belum dibayar.</p>
+{% if payment_url %}<p><a href=\"{{ payment_url }}\">Lanjutkan pembayaran</a></p>{% endif %}
+<p>Abaikan email ini jika Anda sudah menyelesaikan pembayaran.</p>";
+
+const REFUND_SUBJECT: &str = "Refund pesanan {{ order.number }}";
+const REFUND_TEXT: &str = "Halo {{ order.buyer_name }},
+
+Refund untuk pesanan {{ order.number }} ({{ event.name }}) sebesar {{ order.total }} sedang kami proses.
+{% if message %}
+{{ message }}
+{% endif %}
+Tiket pada pesanan ini tidak berlaku lagi.";
+const REFUND_HTML: &str = "<p>Halo {{ order.buyer_name }},</p>
+<p>Refund untuk pesanan {{ order.number }} (<strong>{{ event.name }}</strong>) sebesar {{ order.total }} sedang kami proses.</p>
+{% if message %}<p style=\"white-space: pre-line\">{{ message }}</p>{% endif %}
+<p>Tiket pada pesanan ini tidak berlaku lagi.</p>";
+
+const CANCELLATION_SUBJECT: &str = "Pesanan {{ order.number }} dibatalkan";
+const CANCELLATION_TEXT: &str = "Halo {{ order.buyer_name }},
+
+Pesanan {{ order.number }} untuk {{ event.name }} telah dibatalkan.
+{% if message %}
+{{ message }}
+{% endif %}
+Hubungi kami jika Anda memiliki pertanyaan.";
+const CANCELLATION_HTML: &str = "<p>Halo {{ order.buyer_name }},</p>
+<p>Pesanan {{ order.number }} untuk <strong>{{ event.name }}</strong> telah dibatalkan.</p>
+{% if message %}<p style=\"white-space: pre-line\">{{ message }}</p>{% endif %}
+<p>Hubungi kami jika Anda memiliki pertanyaan.</p>";
+
+const ANNOUNCEMENT_SUBJECT: &str = "Informasi penting: {{ event.name }}";
+const ANNOUNCEMENT_TEXT: &str = "Halo {{ order.buyer_name }},
+
+{{ message }}
+
+{{ event.name }}
+{{ event.date }} ({{ event.timezone }})
+{{ event.location }}
+{{ event.url }}";
+const ANNOUNCEMENT_HTML: &str = "<p>Halo {{ order.buyer_name }},</p>
+<p style=\"white-space: pre-line\">{{ message }}</p>
+<p><strong>{{ event.name }}</strong><br>{{ event.date }} ({{ event.timezone }})<br>{{ event.location }}<br><a href=\"{{ event.url }}\">Lihat detail event</a></p>";
+
+const EVENT_REMINDER_SUBJECT: &str = "Pengingat: {{ event.name }} {{ message }}";
+const EVENT_REMINDER_TEXT: &str = "Halo {{ order.buyer_name }},
+
+{{ event.name }} dimulai {{ message }}.
+
+{{ event.date }} ({{ event.timezone }})
+{{ event.location }}
+{{ event.url }}
+
+Tunjukkan QR tiket berikut di pintu masuk:
+{% for ticket in tickets %}- {{ ticket.code }} ({{ ticket.tier }}): {{ ticket.qr_url }}
+{% endfor %}
+Sampai jumpa di lokasi!";
+const EVENT_REMINDER_HTML: &str = "<p>Halo {{ order.buyer_name }},</p>
+<p><strong>{{ event.name }}</strong> dimulai {{ message }}.</p>
+<p>{{ event.date }} ({{ event.timezone }})<br>{{ event.location }}<br><a href=\"{{ event.url }}\">Lihat detail event</a></p>
+<p>Tunjukkan QR tiket berikut di pintu masuk:</p>
+{% for ticket in tickets %}<p><strong>{{ ticket.code }}</strong> ({{ ticket.tier }})<br><img src=\"{{ ticket.qr_url }}\" alt=\"QR {{ ticket.code }}\" width=\"240\" height=\"240\"></p>
+{% endfor %}<p>Sampai jumpa di lokasi!</p>";
+
+/// Template bawaan yang dipakai selama belum ada template global maupun override event.
+pub fn default_template(kind: EmailTemplateKind) -> EffectiveEmailTemplate {
+    let (subject, text, html) = match kind {
+        EmailTemplateKind::OrderConfirmation => (ORDER_CONFIRMATION_SUBJECT, ORDER_CONFIRMATION_TEXT, ORDER_CONFIRMATION_HTML),
+        EmailTemplateKind::TicketDelivery => (TICKET_DELIVERY_SUBJECT, TICKET_DELIVERY_TEXT, TICKET_DELIVERY_HTML),
+        EmailTemplateKind::PaymentReminder => (PAYMENT_REMINDER_SUBJECT, PAYMENT_REMINDER_TEXT, PAYMENT_REMINDER_HTML),
+        EmailTemplateKind::Refund => (REFUND_SUBJECT, REFUND_TEXT, REFUND_HTML),
+        EmailTemplateKind::Cancellation => (CANCELLATION_SUBJECT, CANCELLATION_TEXT, CANCELLATION_HTML),
+        EmailTemplateKind::Announcement => (ANNOUNCEMENT_SUBJECT, ANNOUNCEMENT_TEXT, ANNOUNCEMENT_HTML),
+        EmailTemplateKind::EventReminder => (EVENT_REMINDER_SUBJECT, EVENT_REMINDER_TEXT, EVENT_REMINDER_HTML),
+    };
+    EffectiveEmailTemplate {
+        kind,
+        source: TemplateSource::Default,
+        subject: subject.to_string(),
+        html: html.to_string(),
+        text: text.to_string(),
+    }
+}
+
+fn effective(template: EmailTemplate) -> EffectiveEmailTemplate {
+    EffectiveEmailTemplate {
+        kind: template.kind,
+        source: if template.event_id.is_some() { TemplateSource::Event } else { TemplateSource::Global },
+        subject: template.subject,
+        html: template.html,
+        text: template.text,
+    }
+}
+
+/// Urutan prioritas: override event, template global, lalu template bawaan.
+pub async fn find_effective_template(
+    db: &Database,
+    kind: EmailTemplateKind,
+    event_id: Option<ObjectId>,
+) -> mongodb::error::Result<EffectiveEmailTemplate> {
+    let collection: Collection<EmailTemplate> = db.collection("email_templates");
+    if let Some(event_id) = event_id {
+        if let Some(template) = collection.find_one(doc! {"kind": kind.as_str(), "event_id": event_id}, None).await? {
+            return Ok(effective(template));
+        }
+    }
+    match collection.find_one(doc! {"kind": kind.as_str(), "event_id": null}, None).await? {
+        Some(template) => Ok(effective(template)),
+        None => Ok(default_template(kind)),
+    }
+}
+
+/// Salin override template event sumber ke event hasil clone.
+pub async fn copy_event_templates(db: &Database, from: ObjectId, to: ObjectId) -> mongodb::error::Result<()> {
+    let collection: Collection<EmailTemplate> = db.collection("email_templates");
+    let overrides: Vec<EmailTemplate> = collection
+        .find(doc! {"event_id": from}, None)
+        .await?
+        .try_collect()
+        .await?;
+    if overrides.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let copies: Vec<EmailTemplate> = overrides
+        .into_iter()
+        .map(|template| EmailTemplate { id: None, event_id: Some(to), updated_at: now, ..template })
+        .collect();
+    collection.insert_many(copies, None).await?;
+    Ok(())
+}
+
+fn format_rupiah(amount: i64) -> String {
+    let digits = amount.abs().to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(c);
+    }
+    format!("Rp {}{}", if amount < 0 { "-" } else { "" }, grouped)
+}
+
+/// Variabel template: `order`, `event`, `tickets`, `message`, `payment_url` dan `organization_name`.
+pub fn email_context(
+    config: &Config,
+    wallet: &WalletPasses,
+    event: &Event,
+    order: &Order,
+    tickets: &[&Ticket],
+    message: &str,
+    payment_url: Option<&str>,
+) -> serde_json::Value {
+    // Harga event disimpan dalam ribuan rupiah (lihat utils::midtrans)
+    let unit_price = event.tier_price(&order.tier).unwrap_or(event.price);
+    let total = (unit_price * 1000.0) as i64 * order.quantity as i64;
+    let tickets: Vec<serde_json::Value> = tickets
+        .iter()
+        .map(|t| {
+            let links = wallet_links(config, wallet, t);
+            json!({
+                "code": t.code,
+                "tier": t.tier,
+                "attendee_name": t.attendee_name,
+                "qr_url": ticket_qr_url(config, t, "png"),
+                "apple_wallet_url": links.apple_wallet_url,
+                "google_wallet_url": links.google_wallet_url,
+            })
+        })
+        .collect();
+
+    json!({
+        "organization_name": config.organization_name,
+        "message": message,
+        "payment_url": payment_url,
+        "order": {
+            "number": order.midtrans_order_id,
+            "buyer_name": order.buyer_name,
+            "buyer_email": order.buyer_email,
+            "buyer_phone": order.buyer_phone,
+            "tier": order.tier,
+            "quantity": order.quantity,
+            "total": format_rupiah(total),
+            "status": order.status.to_string(),
+        },
+        "event": {
+            "name": event.name,
+            "location": event.location,
+            "date": event.local_date().format("%d %b %Y, %H:%M").to_string(),
+            "timezone": event.timezone,
+            "url": event.public_url(&config.public_base_url),
+        },
+        "tickets": tickets,
+    })
+}
+
+/// Data contoh untuk preview dan validasi template sebelum disimpan.
+pub fn sample_context(config: &Config, event: Option<&Event>) -> serde_json::Value {
+    let (name, location, date, timezone, url) = match event {
+        Some(event) => (
+            event.name.clone(),
+            event.location.clone(),
+            event.local_date().format("%d %b %Y, %H:%M").to_string(),
+            event.timezone.clone(),
+            event.public_url(&config.public_base_url),
+        ),
+        None => {
+            let date = (Utc::now() + Duration::days(14)).with_timezone(&chrono_tz::Asia::Jakarta);
+            (
+                "Contoh Event".to_string(),
+                "Jakarta Convention Center".to_string(),
+                date.format("%d %b %Y, %H:%M").to_string(),
+                DEFAULT_TIMEZONE.to_string(),
+                format!("{}/events/contoh-event", config.public_base_url),
+            )
+        }
+    };
+
+    json!({
+        "organization_name": config.organization_name,
+        "message": "Pesan tambahan dari penyelenggara.",
+        "payment_url": "https://app.sandbox.midtrans.com/snap/v2/vtweb/contoh",
+        "order": {
+            "number": "ORDER-CONTOH",
+            "buyer_name": "Budi Santoso",
+            "buyer_email": "budi@example.com",
+            "buyer_phone": "+6281234567890",
+            "tier": "regular",
+            "quantity": 2,
+            "total": format_rupiah(300_000),
+            "status": "paid",
+        },
+        "event": {
+            "name": name,
+            "location": location,
+            "date": date,
+            "timezone": timezone,
+            "url": url,
+        },
+        "tickets": [
+            {
+                "code": "ABCD2345EFGH6789",
+                "tier": "regular",
+                "attendee_name": "Budi Santoso",
+                "qr_url": format!("{}/api/tickets/contoh/qr.png", config.public_base_url),
+                "apple_wallet_url": null,
+                "google_wallet_url": null,
+            },
+        ],
+    })
+}
+
+/// Render subject, HTML (auto-escape) dan teks. Variabel yang tidak dikenal dianggap error
+/// supaya salah ketik ketahuan saat template disimpan, bukan saat email dikirim.
+pub fn render_email(subject: &str, html: &str, text: &str, context: &serde_json::Value) -> Result<RenderedEmail> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+
+    let subject = env.render_named_str("subject.txt", subject, context)?;
+    Ok(RenderedEmail {
+        subject: subject.split_whitespace().collect::<Vec<_>>().join(" "),
+        html: env.render_named_str("body.html", html, context)?,
+        text: env.render_named_str("body.txt", text, context)?,
+    })
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use mongodb::{bson::doc, options::FindOneAndUpdateOptions, Collection, Database};
+use openssl::{hash::MessageDigest, memcmp, pkey::PKey, sign::Signer};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use serde::Deserialize;
+
+use crate::models::email_outbox::OutboxEmail;
+use crate::models::email_suppression::SuppressionReason;
+use crate::models::order::Order;
+use crate::utils::suppressions::suppress_email;
+
+// Batas selisih jam supaya webhook lama tidak bisa diputar ulang
+const TIMESTAMP_TOLERANCE_SECS: i64 = 5 * 60;
+
+/// Header Svix yang dikirim Resend bersama setiap webhook.
+pub struct WebhookSignature {
+    pub id: String,
+    pub timestamp: String,
+    pub signatures: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WebhookSignature {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        match (headers.get_one("svix-id"), headers.get_one("svix-timestamp"), headers.get_one("svix-signature")) {
+            (Some(id), Some(timestamp), Some(signatures)) => Outcome::Success(WebhookSignature {
+                id: id.to_string(),
+                timestamp: timestamp.to_string(),
+                signatures: signatures.to_string(),
+            }),
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/// Verifikasi tanda tangan Svix: HMAC-SHA256 atas `{id}.{timestamp}.{body}` dengan secret
+/// `whsec_...` (base64), dibandingkan dengan setiap `v1,<base64>` di header.
+pub fn verify_webhook_signature(secret: &str, signature: &WebhookSignature, body: &str, now: DateTime<Utc>) -> Result<()> {
+    let timestamp: i64 = signature.timestamp.parse().map_err(|_| anyhow::anyhow!("Invalid webhook timestamp"))?;
+    if (now.timestamp() - timestamp).abs() > TIMESTAMP_TOLERANCE_SECS {
+        return Err(anyhow::anyhow!("Webhook timestamp outside tolerance"));
+    }
+
+    let key = general_purpose::STANDARD.decode(secret.trim_start_matches("whsec_"))?;
+    let key = PKey::hmac(&key)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(format!("{}.{}.{}", signature.id, signature.timestamp, body).as_bytes())?;
+    let expected = signer.sign_to_vec()?;
+
+    let valid = signature
+        .signatures
+        .split_whitespace()
+        .filter_map(|s| s.strip_prefix("v1,"))
+        .filter_map(|s| general_purpose::STANDARD.decode(s).ok())
+        .any(|candidate| candidate.len() == expected.len() && memcmp::eq(&candidate, &expected));
+    if !valid {
+        return Err(anyhow::anyhow!("Invalid webhook signature"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResendBounce {
+    #[serde(rename = "type", default)]
+    pub bounce_type: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResendEmailData {
+    pub email_id: String,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub bounce: Option<ResendBounce>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResendWebhookEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    pub data: ResendEmailData,
+}
+
+/// Terapkan event Resend ke outbox, order, dan daftar suppression. Mengembalikan `false`
+/// untuk jenis event yang tidak dipakai.
+pub async fn apply_resend_event(db: &Database, event: &ResendWebhookEvent) -> Result<bool> {
+    let at = event.created_at.unwrap_or_else(Utc::now).timestamp_millis();
+    let data = &event.data;
+    let bounce_reason = data.bounce.as_ref().and_then(|b| b.message.clone());
+    let hard_bounce = data
+        .bounce
+        .as_ref()
+        .and_then(|b| b.bounce_type.as_deref())
+        .is_none_or(|t| t.eq_ignore_ascii_case("permanent"));
+
+    let update = match event.event_type.as_str() {
+        "email.delivered" => doc! {"$set": {"delivery_status": "delivered", "delivery_updated_at": at}},
+        "email.delivery_delayed" => doc! {"$set": {"delivery_status": "delivery_delayed", "delivery_updated_at": at}},
+        "email.bounced" => doc! {"$set": {"delivery_status": "bounced", "delivery_updated_at": at, "bounce_reason": &bounce_reason}},
+        "email.complained" => doc! {"$set": {"delivery_status": "complained", "delivery_updated_at": at}},
+        "email.opened" => doc! {"$min": {"opened_at": at}},
+        _ => return Ok(false),
+    };
+
+    // Status akhir (bounce/complaint) tidak ditimpa event delivered yang datang terlambat
+    let mut filter = doc! {"provider_message_id": &data.email_id};
+    if matches!(event.event_type.as_str(), "email.delivered" | "email.delivery_delayed") {
+        filter.insert("delivery_status", doc! {"$nin": ["bounced", "complained"]});
+    }
+    let outbox: Collection<OutboxEmail> = db.collection("email_outbox");
+    let email = outbox
+        .find_one_and_update(filter, update, FindOneAndUpdateOptions::default())
+        .await?;
+
+    let recipients = match &email {
+        Some(email) => vec![email.to.clone()],
+        None => data.to.clone(),
+    };
+    let suppression = match event.event_type.as_str() {
+        "email.bounced" => {
+            if let Some(order_id) = email.as_ref().and_then(|e| e.order_id) {
+                db.collection::<Order>("orders")
+                    .update_one(doc! {"_id": order_id}, doc! {"$set": {"email_bounced": true}}, None)
+                    .await?;
+            }
+            // Soft bounce (mis. mailbox penuh) masih boleh dicoba lagi nanti
+            hard_bounce.then_some(SuppressionReason::HardBounce)
+        }
+        "email.complained" => Some(SuppressionReason::Complaint),
+        _ => None,
+    };
+    if let Some(reason) = suppression {
+        for recipient in &recipients {
+            suppress_email(db, recipient, reason, bounce_reason.as_deref(), Some(&data.email_id)).await?;
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    // Contoh dari dokumentasi Svix
+    const SECRET: &str = "whsec_MfKQ9r8GKYqrTwjUPD8ILPZIo2LaLaSw";
+    const BODY: &str = r#"{"test": 2432232314}"#;
+    const VALID: &str = "v1,g0hM9SsE+OTPJTGt/tmIKtSyZlE3uFJELVlNIOLJ1OE=";
+
+    fn signature(signatures: &str) -> WebhookSignature {
+        WebhookSignature {
+            id: "msg_p5jXN8AQM9LWM0D4loKWxJek".to_string(),
+            timestamp: "1614265330".to_string(),
+            signatures: signatures.to_string(),
+        }
+    }
+
+    fn sent_at() -> DateTime<Utc> {
+        Utc.timestamp_opt(1614265330, 0).unwrap()
+    }
+
+    #[test]
+    fn accepts_known_good_signature() {
+        assert!(verify_webhook_signature(SECRET, &signature(VALID), BODY, sent_at()).is_ok());
+        // Header bisa berisi beberapa tanda tangan (rotasi secret); cukup satu yang cocok
+        let rotated = format!("v1,Zm9vYmFy v1a,ignored {}", VALID);
+        assert!(verify_webhook_signature(SECRET, &signature(&rotated), BODY, sent_at()).is_ok());
+    }
+
+    #[test]
+    fn rejects_tampered_body_or_wrong_signature() {
+        assert!(verify_webhook_signature(SECRET, &signature(VALID), r#"{"test": 2432232315}"#, sent_at()).is_err());
+        assert!(verify_webhook_signature(SECRET, &signature("v1,Zm9vYmFy"), BODY, sent_at()).is_err());
+        assert!(verify_webhook_signature(SECRET, &signature(""), BODY, sent_at()).is_err());
+        let other_secret = "whsec_dGhpcy1pcy1hbm90aGVyLXNlY3JldA==";
+        assert!(verify_webhook_signature(other_secret, &signature(VALID), BODY, sent_at()).is_err());
+    }
+
+    #[test]
+    fn rejects_stale_or_future_timestamps() {
+        let tolerance = chrono::Duration::seconds(TIMESTAMP_TOLERANCE_SECS);
+        assert!(verify_webhook_signature(SECRET, &signature(VALID), BODY, sent_at() + tolerance).is_ok());
+        let late = sent_at() + tolerance + chrono::Duration::seconds(1);
+        assert!(verify_webhook_signature(SECRET, &signature(VALID), BODY, late).is_err());
+        let early = sent_at() - tolerance - chrono::Duration::seconds(1);
+        assert!(verify_webhook_signature(SECRET, &signature(VALID), BODY, early).is_err());
+
+        let mut garbage = signature(VALID);
+        garbage.timestamp = "kemarin".to_string();
+        assert!(verify_webhook_signature(SECRET, &garbage, BODY, sent_at()).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+
+pub struct AtomFeed {
+    id: String,
+    title: String,
+    self_url: String,
+    entries: Vec<AtomEntry>,
+}
+
+pub struct AtomEntry {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub summary: String,
+    pub content: String,
+    pub updated: DateTime<Utc>,
+    pub categories: Vec<String>,
+}
+
+impl AtomFeed {
+    pub fn new(id: String, title: String, self_url: String) -> Self {
+        AtomFeed { id, title, self_url, entries: Vec::new() }
+    }
+
+    pub fn add_entry(&mut self, entry: AtomEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn finish(self) -> String {
+        let updated = self
+            .entries
+            .iter()
+            .map(|e| e.updated)
+            .max()
+            .unwrap_or_else(Utc::now);
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str(&format!("  <id>{}</id>\n", escape_xml(&self.id)));
+        xml.push_str(&format!("  <title>{}</title>\n", escape_xml(&self.title)));
+        xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+        xml.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape_xml(&self.self_url)));
+        xml.push_str("  <author><name>Ticketing App</name></author>\n");
+
+        for entry in &self.entries {
+            xml.push_str("  <entry>\n");
+            xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&entry.id)));
+            xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&entry.title)));
+            xml.push_str(&format!("    <link href=\"{}\"/>\n", escape_xml(&entry.url)));
+            xml.push_str(&format!("    <updated>{}</updated>\n", entry.updated.to_rfc3339()));
+            for category in &entry.categories {
+                xml.push_str(&format!("    <category term=\"{}\"/>\n", escape_xml(category)));
+            }
+            xml.push_str(&format!("    <summary>{}</summary>\n", escape_xml(&entry.summary)));
+            xml.push_str(&format!("    <content type=\"text\">{}</content>\n", escape_xml(&entry.content)));
+            xml.push_str("  </entry>\n");
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+}
+
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,232 @@
+use chrono::{DateTime, Duration, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetName, Tz};
+
+use crate::config::Config;
+use crate::models::event::Event;
+
+/// Pengingat bawaan pada undangan kalender tiket.
+const INVITE_ALARM_BEFORE_HOURS: i64 = 24;
+
+/// Penyusun dokumen iCalendar (RFC 5545) sederhana.
+pub struct ICalendar {
+    lines: Vec<String>,
+    timezones: Vec<Tz>,
+}
+
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub location: String,
+    pub start: DateTime<Utc>,
+    pub url: Option<String>,
+    /// Jika diisi, waktu ditulis dalam jam lokal venue (dengan VTIMEZONE), bukan UTC.
+    pub timezone: Option<Tz>,
+    pub organizer: Option<CalendarOrganizer>,
+    pub alarm_before: Option<Duration>,
+}
+
+pub struct CalendarOrganizer {
+    pub name: String,
+    pub email: String,
+}
+
+impl ICalendar {
+    pub fn new(name: &str, timezone: &str) -> Self {
+        let mut calendar = ICalendar { lines: Vec::new(), timezones: Vec::new() };
+        calendar.push("BEGIN:VCALENDAR");
+        calendar.push("VERSION:2.0");
+        calendar.push("PRODID:-//Ticketing App//Events//ID");
+        calendar.push("CALSCALE:GREGORIAN");
+        calendar.push("METHOD:PUBLISH");
+        calendar.push(&format!("X-WR-CALNAME:{}", escape_text(name)));
+        calendar.push(&format!("X-WR-TIMEZONE:{}", timezone));
+        calendar
+    }
+
+    pub fn add_event(&mut self, event: &CalendarEvent) {
+        if let Some(tz) = event.timezone {
+            self.add_timezone(tz, event.start);
+        }
+
+        self.push("BEGIN:VEVENT");
+        self.push(&format!("UID:{}", event.uid));
+        self.push(&format!("DTSTAMP:{}", format_utc(Utc::now())));
+        match event.timezone {
+            Some(tz) => self.push(&format!(
+                "DTSTART;TZID={}:{}",
+                tz.name(),
+                event.start.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+            )),
+            // Tanpa zona waktu, waktu ditulis dalam UTC supaya tidak perlu komponen VTIMEZONE;
+            // kalender klien akan menampilkannya sesuai zona waktu masing-masing.
+            None => self.push(&format!("DTSTART:{}", format_utc(event.start))),
+        }
+        self.push(&format!("SUMMARY:{}", escape_text(&event.summary)));
+        self.push(&format!("DESCRIPTION:{}", escape_text(&event.description)));
+        self.push(&format!("LOCATION:{}", escape_text(&event.location)));
+        if let Some(url) = &event.url {
+            self.push(&format!("URL:{}", url));
+        }
+        if let Some(organizer) = &event.organizer {
+            self.push(&format!(
+                "ORGANIZER;CN=\"{}\":mailto:{}",
+                organizer.name.replace('"', ""),
+                organizer.email
+            ));
+        }
+        if let Some(before) = event.alarm_before {
+            self.push("BEGIN:VALARM");
+            self.push("ACTION:DISPLAY");
+            self.push(&format!("DESCRIPTION:{}", escape_text(&event.summary)));
+            self.push(&format!("TRIGGER:-{}", format_duration(before)));
+            self.push("END:VALARM");
+        }
+        self.push("END:VEVENT");
+    }
+
+    // Satu observance dengan offset saat event berlangsung; cukup untuk event tunggal
+    // dan tetap benar untuk zona waktu tanpa DST seperti WIB/WITA/WIT.
+    fn add_timezone(&mut self, tz: Tz, at: DateTime<Utc>) {
+        if self.timezones.contains(&tz) {
+            return;
+        }
+        self.timezones.push(tz);
+
+        let offset = tz.offset_from_utc_datetime(&at.naive_utc());
+        let seconds = offset.fix().local_minus_utc();
+        let formatted = format!(
+            "{}{:02}{:02}",
+            if seconds < 0 { '-' } else { '+' },
+            seconds.abs() / 3600,
+            (seconds.abs() % 3600) / 60
+        );
+
+        self.push("BEGIN:VTIMEZONE");
+        self.push(&format!("TZID:{}", tz.name()));
+        self.push("BEGIN:STANDARD");
+        self.push("DTSTART:19700101T000000");
+        self.push(&format!("TZOFFSETFROM:{}", formatted));
+        self.push(&format!("TZOFFSETTO:{}", formatted));
+        if let Some(abbreviation) = offset.abbreviation() {
+            self.push(&format!("TZNAME:{}", abbreviation));
+        }
+        self.push("END:STANDARD");
+        self.push("END:VTIMEZONE");
+    }
+
+    pub fn finish(mut self) -> String {
+        self.push("END:VCALENDAR");
+        let mut output = self.lines.join("\r\n");
+        output.push_str("\r\n");
+        output
+    }
+
+    fn push(&mut self, line: &str) {
+        self.lines.push(fold_line(line));
+    }
+}
+
+/// Undangan kalender satu event untuk email tiket & unduhan pembeli.
+pub fn event_invite(config: &Config, event: &Event) -> String {
+    let mut calendar = ICalendar::new(&event.name, &event.timezone);
+    calendar.add_event(&CalendarEvent {
+        uid: format!("event-{}@{}", event.id.map(|id| id.to_hex()).unwrap_or_default(), config.public_host()),
+        summary: event.name.clone(),
+        description: event.description.clone(),
+        location: event.location.clone(),
+        start: event.date,
+        url: Some(event.public_url(&config.public_base_url)),
+        timezone: Some(event.tz()),
+        organizer: Some(CalendarOrganizer {
+            name: config.organization_name.clone(),
+            email: sender_address(&config.mail_from).to_string(),
+        }),
+        alarm_before: Some(Duration::hours(INVITE_ALARM_BEFORE_HOURS)),
+    });
+    calendar.finish()
+}
+
+// MAIL_FROM boleh berbentuk "Nama <email>"
+fn sender_address(from: &str) -> &str {
+    match (from.find('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &from[start + 1..end],
+        _ => from.trim(),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().abs();
+    if minutes % (24 * 60) == 0 {
+        format!("P{}D", minutes / (24 * 60))
+    } else if minutes % 60 == 0 {
+        format!("PT{}H", minutes / 60)
+    } else {
+        format!("PT{}M", minutes)
+    }
+}
+
+pub fn format_utc(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Baris lebih dari 75 oktet dilipat dengan CRLF + spasi
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut octets = 0;
+    for ch in line.chars() {
+        let width = ch.len_utf8();
+        if octets + width > 75 {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(ch);
+        octets += width;
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_text, fold_line};
+
+    fn assert_folded(line: &str) {
+        let folded = fold_line(line);
+        for (i, physical) in folded.split("\r\n").enumerate() {
+            assert!(physical.len() <= 75, "line {} has {} octets", i, physical.len());
+            if i > 0 {
+                assert!(physical.starts_with(' '));
+            }
+        }
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn escapes_rfc5545_text() {
+        assert_eq!(escape_text(r"Gate A; Gate B, C\D"), r"Gate A\; Gate B\, C\\D");
+        assert_eq!(escape_text("Baris 1\r\nBaris 2\nBaris 3"), r"Baris 1\nBaris 2\nBaris 3");
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        let line = format!("SUMMARY:{}", "a".repeat(67));
+        assert_eq!(fold_line(&line), line);
+    }
+
+    #[test]
+    fn folds_at_75_octets_without_splitting_characters() {
+        assert_folded(&format!("DESCRIPTION:{}", "a".repeat(200)));
+        // 2 oktet (é), 3 oktet (日) dan 4 oktet (🎫) per karakter
+        assert_folded(&format!("LOCATION:{}", "é".repeat(80)));
+        assert_folded(&format!("SUMMARY:{}", "日本".repeat(40)));
+        assert_folded(&format!("SUMMARY:Konser {}", "🎫".repeat(30)));
+    }
+}
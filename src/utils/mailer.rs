@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use lettre::message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::json;
+
+use crate::config::Config;
+
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+    pub attachments: Vec<EmailAttachment>,
+}
+
+#[rocket::async_trait]
+pub trait Mailer: Send + Sync {
+    /// Kirim email dan kembalikan message id dari provider/transport.
+    async fn send(&self, message: &EmailMessage) -> Result<String>;
+}
+
+pub struct ResendMailer {
+    api_key: String,
+    from: String,
+    client: reqwest::Client,
+}
+
+impl ResendMailer {
+    pub fn new(api_key: impl Into<String>, from: impl Into<String>) -> Self {
+        ResendMailer {
+            api_key: api_key.into(),
+            from: from.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl Mailer for ResendMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<String> {
+        let attachments: Vec<serde_json::Value> = message
+            .attachments
+            .iter()
+            .map(|a| json!({
+                "filename": a.filename,
+                "content": general_purpose::STANDARD.encode(&a.content),
+                "content_type": a.content_type,
+            }))
+            .collect();
+        let payload = json!({
+            "from": self.from,
+            "to": [message.to],
+            "subject": message.subject,
+            "text": message.text,
+            "html": message.html,
+            "attachments": attachments,
+        });
+
+        let response = self.client
+            .post("https://api.resend.com/emails")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&payload)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let err_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(anyhow::anyhow!("Resend API error: {}", err_text));
+        }
+        let body: serde_json::Value = response.json().await?;
+        Ok(body.get("id").and_then(|id| id.as_str()).unwrap_or_default().to_string())
+    }
+}
+
+fn build_message(from: &Mailbox, message: &EmailMessage) -> Result<Message> {
+    let body = match &message.html {
+        Some(html) => MultiPart::alternative_plain_html(message.text.clone(), html.clone()),
+        None => MultiPart::mixed().singlepart(SinglePart::plain(message.text.clone())),
+    };
+    let mut parts = MultiPart::mixed().multipart(body);
+    for attachment in &message.attachments {
+        let content_type = ContentType::parse(&attachment.content_type)?;
+        parts = parts.singlepart(Attachment::new(attachment.filename.clone()).body(attachment.content.clone(), content_type));
+    }
+
+    Ok(Message::builder()
+        .from(from.clone())
+        .to(message.to.parse()?)
+        .subject(&message.subject)
+        .message_id(None)
+        .multipart(parts)?)
+}
+
+fn message_id(email: &Message) -> String {
+    email
+        .headers()
+        .get_raw("Message-ID")
+        .unwrap_or_default()
+        .trim_matches(|c| c == '<' || c == '>')
+        .to_string()
+}
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+#[rocket::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<String> {
+        let email = build_message(&self.from, message)?;
+        let id = message_id(&email);
+        self.transport.send(email).await?;
+        Ok(id)
+    }
+}
+
+/// Menulis setiap email sebagai file `.eml` di `MAIL_DIR`; untuk development & pengujian.
+pub struct FileMailer {
+    from: Mailbox,
+    transport: AsyncFileTransport<Tokio1Executor>,
+}
+
+#[rocket::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, message: &EmailMessage) -> Result<String> {
+        let email = build_message(&self.from, message)?;
+        Ok(self.transport.send(email).await?)
+    }
+}
+
+// Arc supaya mailer bisa dibawa ke task background (mis. pengiriman otomatis dari webhook)
+pub fn mailer_from_config(config: &Config) -> Result<Arc<dyn Mailer>> {
+    match config.mail_transport.as_str() {
+        "resend" => {
+            if config.resend_api_key.is_empty() {
+                return Err(anyhow::anyhow!("RESEND_API_KEY must be set when MAIL_TRANSPORT=resend"));
+            }
+            Ok(Arc::new(ResendMailer::new(&config.resend_api_key, &config.mail_from)))
+        }
+        "smtp" => {
+            let host = config
+                .smtp_host
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("SMTP_HOST must be set when MAIL_TRANSPORT=smtp"))?;
+            let mut builder = match config.smtp_tls.as_str() {
+                "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+                "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+                "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+                other => return Err(anyhow::anyhow!("Unsupported SMTP_TLS: {}", other)),
+            }
+            .port(config.smtp_port);
+            if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+                builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+            }
+            Ok(Arc::new(SmtpMailer {
+                from: config.mail_from.parse()?,
+                transport: builder.build(),
+            }))
+        }
+        "file" => {
+            std::fs::create_dir_all(&config.mail_dir)?;
+            Ok(Arc::new(FileMailer {
+                from: config.mail_from.parse()?,
+                transport: AsyncFileTransport::new(&config.mail_dir),
+            }))
+        }
+        other => Err(anyhow::anyhow!("Unsupported MAIL_TRANSPORT: {}", other)),
+    }
+}
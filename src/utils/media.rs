@@ -0,0 +1,49 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use image::{ImageFormat, ImageOutputFormat};
+
+pub const THUMBNAIL_SIZE: u32 = 400;
+
+pub struct ProcessedImage {
+    pub extension: &'static str,
+    pub content_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub thumbnail: Vec<u8>,
+}
+
+/// Validasi file gambar (format & ukuran) lalu buat thumbnail JPEG.
+pub fn process_image(bytes: &[u8], max_bytes: u64) -> Result<ProcessedImage> {
+    if bytes.is_empty() {
+        return Err(anyhow::anyhow!("File is empty"));
+    }
+    if bytes.len() as u64 > max_bytes {
+        return Err(anyhow::anyhow!("File exceeds maximum size of {} bytes", max_bytes));
+    }
+
+    let format = image::guess_format(bytes).map_err(|_| anyhow::anyhow!("Unrecognized image format"))?;
+    let (extension, content_type) = match format {
+        ImageFormat::Jpeg => ("jpg", "image/jpeg"),
+        ImageFormat::Png => ("png", "image/png"),
+        ImageFormat::WebP => ("webp", "image/webp"),
+        _ => return Err(anyhow::anyhow!("Only JPEG, PNG and WebP images are allowed")),
+    };
+
+    let image = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| anyhow::anyhow!("Failed to decode image: {}", e))?;
+
+    let mut thumbnail = Vec::new();
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgb8()
+        .write_to(&mut Cursor::new(&mut thumbnail), ImageOutputFormat::Jpeg(80))?;
+
+    Ok(ProcessedImage {
+        extension,
+        content_type,
+        width: image.width(),
+        height: image.height(),
+        thumbnail,
+    })
+}
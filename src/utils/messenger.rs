@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Utc;
+use rocket::tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::models::order::NotificationChannel;
+
+#[rocket::async_trait]
+pub trait TextMessenger: Send + Sync {
+    /// Kirim pesan singkat ke nomor E.164 dan kembalikan message id dari provider.
+    async fn send(&self, channel: NotificationChannel, to: &str, body: &str) -> Result<String>;
+}
+
+/// SMS dan WhatsApp lewat Twilio Programmable Messaging.
+pub struct TwilioMessenger {
+    account_sid: String,
+    auth_token: String,
+    sms_from: Option<String>,
+    whatsapp_from: Option<String>,
+    client: reqwest::Client,
+}
+
+#[rocket::async_trait]
+impl TextMessenger for TwilioMessenger {
+    async fn send(&self, channel: NotificationChannel, to: &str, body: &str) -> Result<String> {
+        let (from, to) = match channel {
+            NotificationChannel::Sms => (self.sms_from.clone(), to.to_string()),
+            NotificationChannel::Whatsapp => (
+                self.whatsapp_from.as_ref().map(|from| format!("whatsapp:{}", from)),
+                format!("whatsapp:{}", to),
+            ),
+            NotificationChannel::Email => return Err(anyhow::anyhow!("Email is not a text channel")),
+        };
+        let from = from.ok_or_else(|| anyhow::anyhow!("No Twilio sender configured for {:?}", channel))?;
+
+        let response = self.client
+            .post(format!("https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json", self.account_sid))
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[("From", from.as_str()), ("To", to.as_str()), ("Body", body)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let err_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(anyhow::anyhow!("Twilio API error: {}", err_text));
+        }
+        let body: serde_json::Value = response.json().await?;
+        Ok(body.get("sid").and_then(|sid| sid.as_str()).unwrap_or_default().to_string())
+    }
+}
+
+/// Menulis setiap pesan sebagai satu baris JSON di `NOTIFY_DIR/messages.jsonl`; untuk development & pengujian.
+pub struct StubMessenger {
+    path: std::path::PathBuf,
+}
+
+#[rocket::async_trait]
+impl TextMessenger for StubMessenger {
+    async fn send(&self, channel: NotificationChannel, to: &str, body: &str) -> Result<String> {
+        let id = Uuid::new_v4().to_string();
+        let line = json!({"id": id, "channel": channel, "to": to, "body": body, "at": Utc::now()});
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        file.write_all(format!("{}\n", line).as_bytes()).await?;
+        Ok(id)
+    }
+}
+
+/// `None` jika `NOTIFY_PROVIDER=none` (notifikasi SMS/WhatsApp mati).
+pub fn messenger_from_config(config: &Config) -> Result<Option<Arc<dyn TextMessenger>>> {
+    match config.notify_provider.as_str() {
+        "none" => Ok(None),
+        "twilio" => {
+            let (Some(account_sid), Some(auth_token)) = (&config.twilio_account_sid, &config.twilio_auth_token) else {
+                return Err(anyhow::anyhow!("TWILIO_ACCOUNT_SID and TWILIO_AUTH_TOKEN must be set when NOTIFY_PROVIDER=twilio"));
+            };
+            Ok(Some(Arc::new(TwilioMessenger {
+                account_sid: account_sid.clone(),
+                auth_token: auth_token.clone(),
+                sms_from: config.twilio_sms_from.clone(),
+                whatsapp_from: config.twilio_whatsapp_from.clone(),
+                client: reqwest::Client::new(),
+            })))
+        }
+        "stub" => {
+            std::fs::create_dir_all(&config.notify_dir)?;
+            Ok(Some(Arc::new(StubMessenger {
+                path: std::path::Path::new(&config.notify_dir).join("messages.jsonl"),
+            })))
+        }
+        other => Err(anyhow::anyhow!("Unsupported NOTIFY_PROVIDER: {}", other)),
+    }
+}
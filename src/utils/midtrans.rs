@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use reqwest::Client;
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
+use openssl::{memcmp, sha::sha512};
 
 use crate::models::{event::Event, order::Order};
 
@@ -40,6 +41,49 @@ pub struct MidtransChargeResponse {
     pub redirect_url: String,
 }
 
+fn basic_auth(server_key: &str) -> String {
+    format!("Basic {}", general_purpose::STANDARD.encode(format!("{}:", server_key)))
+}
+
+/// Verifikasi `signature_key` notifikasi Midtrans:
+/// SHA512(order_id + status_code + gross_amount + server key) dalam hex.
+pub fn verify_notification_signature(payload: &serde_json::Value, server_key: &str) -> bool {
+    let field = |name: &str| payload.get(name).and_then(|v| v.as_str());
+    let (Some(order_id), Some(status_code), Some(gross_amount), Some(signature)) = (
+        field("order_id"),
+        field("status_code"),
+        field("gross_amount"),
+        field("signature_key"),
+    ) else {
+        return false;
+    };
+
+    let digest = sha512(format!("{}{}{}{}", order_id, status_code, gross_amount, server_key).as_bytes());
+    let expected: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    let signature = signature.to_ascii_lowercase();
+    signature.len() == expected.len() && memcmp::eq(signature.as_bytes(), expected.as_bytes())
+}
+
+/// Status transaksi langsung dari Status API Midtrans (mis. `settlement`, `expire`).
+pub async fn fetch_transaction_status(order_id: &str, server_key: &str) -> Result<String> {
+    let response = Client::new()
+        .get(format!("https://api.sandbox.midtrans.com/v2/{}/status", order_id))
+        .header("Authorization", basic_auth(server_key))
+        .header("Accept", "application/json")
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let err_text = response.text().await?;
+        return Err(anyhow::anyhow!("Midtrans API error: {}", err_text));
+    }
+    let body: serde_json::Value = response.json().await?;
+    body.get("transaction_status")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Midtrans status response has no transaction_status"))
+}
+
 pub async fn create_midtrans_transaction(
     order: &Order,
     event: &Event,
@@ -48,16 +92,25 @@ pub async fn create_midtrans_transaction(
     let client = Client::new();
     let url = "https://app.sandbox.midtrans.com/snap/v1/transactions";
 
+    let unit_price = event.tier_price(&order.tier).unwrap_or(event.price);
+    let item_price = (unit_price * 1000.0) as i64;
+
     let transaction_details = MidtransTransactionDetail {
         order_id: order.midtrans_order_id.clone(),
-        gross_amount: (event.price * 1000.0) as i64,
+        gross_amount: item_price * order.quantity as i64,
+    };
+
+    let item_name = if event.tiers.is_empty() {
+        event.name.clone()
+    } else {
+        format!("{} - {}", event.name, order.tier)
     };
 
     let item_details = vec![MidtransItemDetail {
         id: event.id.as_ref().unwrap().to_hex(),
-        price: (event.price * 1000.0) as i64,
-        quantity: 1,
-        name: event.name.clone(),
+        price: item_price,
+        quantity: order.quantity,
+        name: item_name,
     }];
 
     let customer_details = MidtransCustomerDetail {
@@ -73,13 +126,9 @@ pub async fn create_midtrans_transaction(
         customer_details,
     };
 
-    let credentials = format!("{}:", server_key);
-    let encoded = general_purpose::STANDARD.encode(credentials);
-    let auth_header = format!("Basic {}", encoded);
-
     let response = client
         .post(url)
-        .header("Authorization", auth_header)
+        .header("Authorization", basic_auth(server_key))
         .header("Content-Type", "application/json")
         .json(&charge_payload)
         .send()
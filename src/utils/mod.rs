@@ -1,2 +1,28 @@
+pub mod access;
+pub mod announcements;
+pub mod attendance;
 pub mod auth;
-pub mod midtrans;
\ No newline at end of file
+pub mod delivery;
+pub mod email_templates;
+pub mod email_webhook;
+pub mod feed;
+pub mod ical;
+pub mod mailer;
+pub mod media;
+pub mod messenger;
+pub mod midtrans;
+pub mod notifier;
+pub mod outbox;
+pub mod pdf;
+pub mod phone;
+pub mod qr;
+pub mod rate_limit;
+pub mod recovery;
+pub mod reminders;
+pub mod signing;
+pub mod slug;
+pub mod storage;
+pub mod suppressions;
+pub mod tickets;
+pub mod time;
+pub mod wallet;
\ No newline at end of file
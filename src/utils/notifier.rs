@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::models::{event::Event, order::{NotificationChannel, Order}, ticket::Ticket};
+use crate::utils::messenger::TextMessenger;
+use crate::utils::phone::normalize_phone;
+use crate::utils::tickets::ticket_resource_url;
+
+/// Notifikasi SMS/WhatsApp ke pembeli sesuai `notification_channel` order. Pesan dikirim di
+/// background tanpa retry; email tetap menjadi saluran utama.
+#[derive(Clone)]
+pub struct Notifier {
+    messenger: Option<Arc<dyn TextMessenger>>,
+    country_code: String,
+}
+
+impl Notifier {
+    pub fn new(messenger: Option<Arc<dyn TextMessenger>>, config: &Config) -> Self {
+        Notifier { messenger, country_code: config.phone_country_code.clone() }
+    }
+
+    pub fn normalize_phone(&self, phone: &str) -> Option<String> {
+        normalize_phone(phone, &self.country_code)
+    }
+
+    pub fn notify(&self, order: &Order, body: String) {
+        let channel = order.notification_channel;
+        if channel == NotificationChannel::Email {
+            return;
+        }
+        let Some(messenger) = self.messenger.clone() else {
+            return;
+        };
+        let Some(to) = self.normalize_phone(&order.buyer_phone) else {
+            println!("⚠️ Order {} has no valid phone number for {:?}", order.midtrans_order_id, channel);
+            return;
+        };
+
+        let order_number = order.midtrans_order_id.clone();
+        rocket::tokio::spawn(async move {
+            match messenger.send(channel, &to, &body).await {
+                Ok(id) => println!("📱 Sent {:?} notification for order {} ({})", channel, order_number, id),
+                Err(e) => println!("❌ Failed to send {:?} notification for order {}: {:?}", channel, order_number, e),
+            }
+        });
+    }
+}
+
+fn event_when(event: &Event) -> String {
+    format!("{} ({})", event.local_date().format("%d %b %Y, %H:%M"), event.timezone)
+}
+
+pub fn order_created_text(event: &Event, order: &Order, payment_url: &str) -> String {
+    format!(
+        "Pesanan {} untuk {} ({} tiket {}) menunggu pembayaran. Bayar di: {}",
+        order.midtrans_order_id, event.name, order.quantity, order.tier, payment_url
+    )
+}
+
+pub fn tickets_ready_text(config: &Config, event: &Event, order: &Order, tickets: &[&Ticket]) -> String {
+    let mut text = format!(
+        "Tiket {} - {} sudah dikirim ke {}.",
+        event.name,
+        event_when(event),
+        order.buyer_email
+    );
+    for ticket in tickets {
+        text.push_str(&format!("\n{}: {}", ticket.code, ticket_resource_url(config, ticket, "ticket.pdf")));
+    }
+    text
+}
+
+pub fn event_reminder_text(event: &Event, when: &str) -> String {
+    format!(
+        "Pengingat: {} dimulai {}, {} di {}. Siapkan QR tiket Anda.",
+        event.name,
+        when,
+        event_when(event),
+        event.location
+    )
+}
@@ -0,0 +1,269 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId, Document}, options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument}, Collection, Database};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::{select, sync::Notify, time::sleep};
+use rocket::{Orbit, Rocket, Shutdown};
+
+use crate::config::Config;
+use crate::models::email_outbox::{OutboxAttachment, OutboxEmail, OutboxStatus};
+use crate::models::email_template::EmailTemplateKind;
+use crate::models::order::Order;
+use crate::utils::mailer::{EmailAttachment, EmailMessage, Mailer};
+use crate::utils::suppressions::is_suppressed;
+
+// Email yang berstatus `sending` lebih lama dari ini dianggap ditinggal worker yang mati
+const SENDING_LEASE_MINUTES: i64 = 5;
+const MAX_RETRY_DELAY_HOURS: i64 = 6;
+const LIST_LIMIT: i64 = 200;
+
+/// Asal email di outbox, untuk pelacakan per order, event, dan pengumuman.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutboxSource {
+    pub order_id: Option<ObjectId>,
+    pub event_id: Option<ObjectId>,
+    pub announcement_id: Option<ObjectId>,
+}
+
+/// Antrean email tahan restart: setiap email disimpan dulu di `email_outbox`,
+/// lalu dikirim worker background dengan retry exponential backoff.
+#[derive(Clone)]
+pub struct EmailOutbox {
+    db: Database,
+    mailer: Arc<dyn Mailer>,
+    notify: Arc<Notify>,
+    max_attempts: u32,
+    retry_base: Duration,
+    poll_interval: std::time::Duration,
+}
+
+impl EmailOutbox {
+    pub fn new(db: Database, mailer: Arc<dyn Mailer>, config: &Config) -> Self {
+        EmailOutbox {
+            db,
+            mailer,
+            notify: Arc::new(Notify::new()),
+            max_attempts: config.outbox_max_attempts.max(1),
+            retry_base: Duration::seconds(config.outbox_retry_base_secs as i64),
+            poll_interval: std::time::Duration::from_secs(config.outbox_poll_secs.max(1)),
+        }
+    }
+
+    fn collection(&self) -> Collection<OutboxEmail> {
+        self.db.collection("email_outbox")
+    }
+
+    /// Simpan email ke outbox dan bangunkan worker; mengembalikan id outbox.
+    pub async fn enqueue(
+        &self,
+        kind: &str,
+        source: OutboxSource,
+        message: EmailMessage,
+    ) -> mongodb::error::Result<ObjectId> {
+        let now = Utc::now();
+        let email = OutboxEmail {
+            id: None,
+            kind: kind.to_string(),
+            order_id: source.order_id,
+            event_id: source.event_id,
+            announcement_id: source.announcement_id,
+            to: message.to,
+            subject: message.subject,
+            text: message.text,
+            html: message.html,
+            attachments: message
+                .attachments
+                .into_iter()
+                .map(|a| OutboxAttachment { filename: a.filename, content_type: a.content_type, content: a.content })
+                .collect(),
+            status: OutboxStatus::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            provider_message_id: None,
+            created_at: now,
+            sent_at: None,
+            delivery_status: None,
+            bounce_reason: None,
+            delivery_updated_at: None,
+            opened_at: None,
+        };
+        let result = self.collection().insert_one(&email, None).await?;
+        self.notify.notify_one();
+        Ok(result.inserted_id.as_object_id().unwrap_or_default())
+    }
+
+    pub async fn list(&self, filter: Document) -> mongodb::error::Result<Vec<OutboxEmail>> {
+        let find_options = FindOptions::builder()
+            .sort(doc! {"created_at": -1})
+            .limit(LIST_LIMIT)
+            .build();
+        self.collection().find(filter, find_options).await?.try_collect().await
+    }
+
+    /// Jadwalkan ulang email yang gagal (dead), tertahan suppression, atau masih menunggu retry
+    /// untuk dikirim sekarang, dengan jatah percobaan baru.
+    pub async fn retry(&self, id: ObjectId) -> mongodb::error::Result<Option<OutboxEmail>> {
+        let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+        let updated = self
+            .collection()
+            .find_one_and_update(
+                doc! {"_id": id, "status": {"$in": ["dead", "suppressed", "pending"]}},
+                doc! {"$set": {"status": "pending", "attempts": 0, "next_attempt_at": Utc::now().timestamp_millis()}},
+                options,
+            )
+            .await?;
+        if updated.is_some() {
+            self.notify.notify_one();
+        }
+        Ok(updated)
+    }
+
+    /// Ambil satu email yang jatuh tempo (atau yang lease `sending`-nya habis) dan tandai `sending`.
+    async fn claim_next(&self) -> mongodb::error::Result<Option<OutboxEmail>> {
+        let now = Utc::now();
+        let options = FindOneAndUpdateOptions::builder()
+            .sort(doc! {"next_attempt_at": 1})
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection()
+            .find_one_and_update(
+                doc! {
+                    "status": {"$in": ["pending", "sending"]},
+                    "next_attempt_at": {"$lte": now.timestamp_millis()},
+                },
+                doc! {
+                    "$set": {
+                        "status": "sending",
+                        "next_attempt_at": (now + Duration::minutes(SENDING_LEASE_MINUTES)).timestamp_millis(),
+                    },
+                    "$inc": {"attempts": 1},
+                },
+                options,
+            )
+            .await
+    }
+
+    fn retry_delay(&self, attempts: u32) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(16);
+        (self.retry_base * 2i32.pow(exponent)).min(Duration::hours(MAX_RETRY_DELAY_HOURS))
+    }
+
+    async fn process(&self, email: OutboxEmail) -> Result<()> {
+        let id = email.id.ok_or_else(|| anyhow::anyhow!("Outbox email has no id"))?;
+        // Dicek saat kirim (bukan saat enqueue) supaya email yang sudah antre ikut tertahan
+        if is_suppressed(&self.db, &email.to).await? {
+            println!("🚫 Email {} to {} suppressed", id, email.to);
+            self.collection()
+                .update_one(doc! {"_id": id}, doc! {"$set": {"status": "suppressed"}}, None)
+                .await?;
+            return Ok(());
+        }
+        let message = EmailMessage {
+            to: email.to.clone(),
+            subject: email.subject.clone(),
+            text: email.text.clone(),
+            html: email.html.clone(),
+            attachments: email
+                .attachments
+                .iter()
+                .map(|a| EmailAttachment { filename: a.filename.clone(), content_type: a.content_type.clone(), content: a.content.clone() })
+                .collect(),
+        };
+
+        match self.mailer.send(&message).await {
+            Ok(message_id) => {
+                self.collection()
+                    .update_one(
+                        doc! {"_id": id},
+                        doc! {"$set": {
+                            "status": "sent",
+                            "provider_message_id": &message_id,
+                            "sent_at": Utc::now().timestamp_millis(),
+                            "last_error": null,
+                        }},
+                        None,
+                    )
+                    .await?;
+                self.after_sent(&email).await?;
+                println!("📧 Sent {} email {} to {} ({})", email.kind, id, email.to, message_id);
+            }
+            Err(e) => {
+                let error = e.to_string();
+                let update = if email.attempts >= self.max_attempts {
+                    println!("❌ Email {} dead after {} attempt(s): {}", id, email.attempts, error);
+                    doc! {"$set": {"status": "dead", "last_error": &error}}
+                } else {
+                    let next_attempt_at = Utc::now() + self.retry_delay(email.attempts);
+                    println!("⚠️ Email {} failed (attempt {}), retrying at {}: {}", id, email.attempts, next_attempt_at, error);
+                    doc! {"$set": {"status": "pending", "last_error": &error, "next_attempt_at": next_attempt_at.timestamp_millis()}}
+                };
+                self.collection().update_one(doc! {"_id": id}, update, None).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Order baru dianggap `sent` setelah email tiketnya benar-benar terkirim
+    async fn after_sent(&self, email: &OutboxEmail) -> mongodb::error::Result<()> {
+        if let (Some(order_id), true) = (email.order_id, email.kind == EmailTemplateKind::TicketDelivery.as_str()) {
+            self.db
+                .collection::<Order>("orders")
+                .update_one(
+                    doc! {"_id": order_id, "status": {"$in": ["paid", "sent"]}},
+                    doc! {"$set": {"status": "sent"}},
+                    None,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Loop worker: kirim semua email yang jatuh tempo, lalu tunggu enqueue baru atau interval polling.
+    pub async fn run(self, mut shutdown: Shutdown) {
+        loop {
+            loop {
+                match self.claim_next().await {
+                    Ok(Some(email)) => {
+                        if let Err(e) = self.process(email).await {
+                            println!("❌ Email outbox error: {:?}", e);
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        println!("❌ Failed to read email outbox: {:?}", e);
+                        break;
+                    }
+                }
+            }
+
+            select! {
+                _ = self.notify.notified() => {},
+                _ = sleep(self.poll_interval) => {},
+                _ = &mut shutdown => break,
+            }
+        }
+    }
+}
+
+/// Menjalankan worker outbox begitu Rocket mulai melayani request.
+pub struct OutboxWorker;
+
+#[rocket::async_trait]
+impl Fairing for OutboxWorker {
+    fn info(&self) -> Info {
+        Info {
+            name: "Email outbox worker",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        if let Some(outbox) = rocket.state::<EmailOutbox>() {
+            rocket::tokio::spawn(outbox.clone().run(rocket.shutdown()));
+        }
+    }
+}
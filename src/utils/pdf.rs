@@ -0,0 +1,170 @@
+use anyhow::Result;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+
+use crate::models::event::{Event, TicketPaper};
+use crate::models::order::Order;
+use crate::models::ticket::Ticket;
+use crate::utils::qr;
+
+pub struct TicketPage<'a> {
+    pub ticket: &'a Ticket,
+    pub qr_payload: String,
+}
+
+const REGULAR_FONT: Name = Name(b"F1");
+const BOLD_FONT: Name = Name(b"F2");
+
+/// Render satu halaman per tiket mengikuti `ticket_layout` milik event.
+pub fn render_ticket_pdf(event: &Event, order: &Order, pages: &[TicketPage]) -> Result<Vec<u8>> {
+    let layout = &event.ticket_layout;
+    let (width, height) = match layout.paper {
+        TicketPaper::A4 => (595.0, 842.0),
+        TicketPaper::A6 => (298.0, 420.0),
+    };
+    let accent = layout.accent_rgb().unwrap_or((0.12, 0.23, 0.54));
+
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let regular_font_id = Ref::new(3);
+    let bold_font_id = Ref::new(4);
+    let page_ids: Vec<Ref> = (0..pages.len()).map(|i| Ref::new(5 + i as i32 * 2)).collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id).kids(page_ids.iter().copied()).count(pages.len() as i32);
+    pdf.type1_font(regular_font_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_font_id)
+        .base_font(Name(b"Helvetica-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+    let date = event.local_date().format("%d %B %Y, %H:%M %Z").to_string();
+
+    for (page, page_id) in pages.iter().zip(&page_ids) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page_writer = pdf.page(*page_id);
+        page_writer
+            .parent(page_tree_id)
+            .media_box(Rect::new(0.0, 0.0, width, height))
+            .contents(content_id);
+        page_writer
+            .resources()
+            .fonts()
+            .pair(REGULAR_FONT, regular_font_id)
+            .pair(BOLD_FONT, bold_font_id);
+        page_writer.finish();
+
+        // Semua ukuran diskalakan dari layout A4
+        let k = width / 595.0;
+        let margin = 40.0 * k;
+        let mut content = Content::new();
+
+        content.set_fill_rgb(accent.0, accent.1, accent.2);
+        content.rect(0.0, height - 80.0 * k, width, 80.0 * k);
+        content.fill_nonzero();
+
+        content.set_fill_rgb(1.0, 1.0, 1.0);
+        text(&mut content, BOLD_FONT, 11.0 * k, margin, height - 28.0 * k, "E-TICKET");
+        text(&mut content, BOLD_FONT, 20.0 * k, margin, height - 58.0 * k, &event.name);
+
+        content.set_fill_rgb(0.0, 0.0, 0.0);
+        let rows = [
+            ("Tanggal", date.as_str()),
+            ("Lokasi", event.location.as_str()),
+            ("Tier", page.ticket.tier.as_str()),
+            ("Nama", page.ticket.attendee_name.as_str()),
+            ("No. Order", order.midtrans_order_id.as_str()),
+            ("Kode Tiket", page.ticket.code.as_str()),
+        ];
+        let mut y = height - 115.0 * k;
+        for (label, value) in rows {
+            text(&mut content, BOLD_FONT, 11.0 * k, margin, y, label);
+            text(&mut content, REGULAR_FONT, 11.0 * k, margin + 90.0 * k, y, value);
+            y -= 20.0 * k;
+        }
+
+        let qr_size = 220.0 * k;
+        let qr_top = y - 10.0 * k;
+        draw_qr(&mut content, &page.qr_payload, (width - qr_size) / 2.0, qr_top - qr_size, qr_size)?;
+        y = qr_top - qr_size - 30.0 * k;
+
+        if layout.show_description {
+            let max_chars = ((width - margin * 2.0) / (5.2 * k)) as usize;
+            for line in wrap(&event.description, max_chars).into_iter().take(8) {
+                text(&mut content, REGULAR_FONT, 10.0 * k, margin, y, &line);
+                y -= 14.0 * k;
+            }
+        }
+
+        if let Some(footer) = &layout.footer_text {
+            content.set_fill_rgb(0.4, 0.4, 0.4);
+            text(&mut content, REGULAR_FONT, 9.0 * k, margin, 30.0 * k, footer);
+        }
+
+        pdf.stream(content_id, &content.finish());
+    }
+
+    Ok(pdf.finish())
+}
+
+fn text(content: &mut Content, font: Name, size: f32, x: f32, y: f32, value: &str) {
+    content.begin_text();
+    content.set_font(font, size);
+    content.next_line(x, y);
+    content.show(Str(&win_ansi(value)));
+    content.end_text();
+}
+
+fn draw_qr(content: &mut Content, payload: &str, x: f32, y: f32, size: f32) -> Result<()> {
+    let (width, dark) = qr::modules(payload)?;
+    // Quiet zone 4 modul di setiap sisi
+    let module = size / (width + 8) as f32;
+    let origin_x = x + module * 4.0;
+    let origin_y = y + size - module * 4.0;
+
+    content.set_fill_rgb(1.0, 1.0, 1.0);
+    content.rect(x, y, size, size);
+    content.fill_nonzero();
+    content.set_fill_rgb(0.0, 0.0, 0.0);
+    for (index, _) in dark.iter().enumerate().filter(|(_, d)| **d) {
+        let column = (index % width) as f32;
+        let row = (index / width) as f32;
+        content.rect(origin_x + column * module, origin_y - (row + 1.0) * module, module, module);
+    }
+    content.fill_nonzero();
+    Ok(())
+}
+
+// Font standar PDF hanya mendukung WinAnsi; karakter di luar Latin-1 diganti '?'
+fn win_ansi(value: &str) -> Vec<u8> {
+    value
+        .chars()
+        .map(|c| match c {
+            '\u{2013}' | '\u{2014}' => b'-',
+            '\u{2018}' | '\u{2019}' => b'\'',
+            '\u{201C}' | '\u{201D}' => b'"',
+            c if (c as u32) < 0x20 => b' ',
+            c if (c as u32) <= 0xFF => c as u32 as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+fn wrap(value: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in value.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + word.chars().count() + 1 > max_chars {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
@@ -0,0 +1,62 @@
+/// Normalisasi nomor telepon ke format E.164 (`+628123456789`). Nomor lokal diawali `0`
+/// (atau langsung `8` seperti nomor seluler Indonesia) diberi kode negara `country_code`.
+pub fn normalize_phone(raw: &str, country_code: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    let digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+    if trimmed.chars().any(|c| !(c.is_ascii_digit() || " -.()+".contains(c))) {
+        return None;
+    }
+    // `+` hanya sah sebagai karakter pertama
+    if trimmed.chars().skip(1).any(|c| c == '+') {
+        return None;
+    }
+
+    let international = if trimmed.starts_with('+') {
+        digits
+    } else if let Some(rest) = digits.strip_prefix("00") {
+        rest.to_string()
+    } else if let Some(rest) = digits.strip_prefix('0') {
+        format!("{}{}", country_code, rest)
+    } else if digits.starts_with(country_code) {
+        digits
+    } else if digits.starts_with('8') && country_code == "62" {
+        format!("62{}", digits)
+    } else {
+        return None;
+    };
+
+    // E.164: maksimal 15 digit dan tidak diawali 0
+    if !(8..=15).contains(&international.len()) || international.starts_with('0') {
+        return None;
+    }
+    Some(format!("+{}", international))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_phone;
+
+    #[test]
+    fn normalizes_to_e164() {
+        let cases = [
+            ("0812-3456-7890", Some("+6281234567890")),
+            ("08123456789", Some("+628123456789")),
+            ("628123456789", Some("+628123456789")),
+            ("+62 812 3456 789", Some("+628123456789")),
+            ("+1 (415) 555-0123", Some("+14155550123")),
+            ("0062 812 3456 789", Some("+628123456789")),
+            ("8123456789", Some("+628123456789")),
+            ("0812", None),
+            ("+62 12", None),
+            ("+62812345678901234", None),
+            ("62+8123456789", None),
+            ("+62+8123456789", None),
+            ("0812-abc-789", None),
+            ("", None),
+            ("hubungi saya", None),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(normalize_phone(raw, "62").as_deref(), expected, "input {:?}", raw);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::Result;
+use image::{ImageBuffer, ImageOutputFormat, Luma};
+use qrcode::{render::svg, Color, EcLevel, QrCode};
+use std::io::Cursor;
+
+const MODULE_PIXELS: u32 = 8;
+const QUIET_ZONE: u32 = 4;
+
+fn encode(payload: &str) -> Result<QrCode> {
+    QrCode::with_error_correction_level(payload.as_bytes(), EcLevel::M)
+        .map_err(|e| anyhow::anyhow!("Failed to encode QR code: {:?}", e))
+}
+
+/// Matriks modul QR (baris demi baris, `true` = gelap) beserta lebarnya.
+pub fn modules(payload: &str) -> Result<(usize, Vec<bool>)> {
+    let code = encode(payload)?;
+    let dark = code.to_colors().into_iter().map(|c| c == Color::Dark).collect();
+    Ok((code.width(), dark))
+}
+
+pub fn render_png(payload: &str) -> Result<Vec<u8>> {
+    let code = encode(payload)?;
+    let width = code.width() as u32;
+    let colors = code.to_colors();
+    let size = (width + QUIET_ZONE * 2) * MODULE_PIXELS;
+
+    let image = ImageBuffer::from_fn(size, size, |x, y| {
+        let module_x = (x / MODULE_PIXELS) as i64 - QUIET_ZONE as i64;
+        let module_y = (y / MODULE_PIXELS) as i64 - QUIET_ZONE as i64;
+        let inside = (0..width as i64).contains(&module_x) && (0..width as i64).contains(&module_y);
+        if inside && colors[(module_y * width as i64 + module_x) as usize] == Color::Dark {
+            Luma([0u8])
+        } else {
+            Luma([255u8])
+        }
+    });
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+    Ok(png)
+}
+
+pub fn render_svg(payload: &str) -> Result<String> {
+    let code = encode(payload)?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(240, 240)
+        .quiet_zone(true)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use chrono::Utc;
+    use mongodb::bson::oid::ObjectId;
+    use resvg::{tiny_skia, usvg};
+
+    use crate::models::ticket::{Ticket, TicketStatus};
+    use crate::utils::signing::TicketSigner;
+    use crate::utils::tickets::ticket_qr_payload;
+
+    fn decode_greyscale(width: usize, height: usize, pixel: impl FnMut(usize, usize) -> u8) -> String {
+        let mut image = rqrr::PreparedImage::prepare_from_greyscale(width, height, pixel);
+        let grids = image.detect_grids();
+        assert_eq!(grids.len(), 1, "expected exactly one QR code");
+        let (_, content) = grids[0].decode().expect("QR code should decode");
+        content
+    }
+
+    fn decode_png(png: &[u8]) -> String {
+        let image = image::load_from_memory(png).expect("valid PNG").to_luma8();
+        decode_greyscale(image.width() as usize, image.height() as usize, |x, y| image.get_pixel(x as u32, y as u32)[0])
+    }
+
+    fn decode_svg(svg: &str) -> String {
+        let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).expect("valid SVG");
+        let size = tree.size().to_int_size();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).expect("non-empty SVG");
+        pixmap.fill(tiny_skia::Color::WHITE);
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+        decode_greyscale(size.width() as usize, size.height() as usize, |x, y| {
+            pixmap.pixel(x as u32, y as u32).map_or(255, |p| p.red())
+        })
+    }
+
+    fn signer() -> TicketSigner {
+        let keys = format!("k1:{},k2:{}", STANDARD.encode([1u8; 32]), STANDARD.encode([2u8; 32]));
+        TicketSigner::from_keys(&keys, Some("k2")).unwrap()
+    }
+
+    fn ticket() -> Ticket {
+        Ticket {
+            id: Some(ObjectId::new()),
+            order_id: ObjectId::new(),
+            event_id: ObjectId::new(),
+            seq: 0,
+            code: "ABCD2345EFGH6789".to_string(),
+            access_token: "token".to_string(),
+            attendee_name: "Budi".to_string(),
+            tier: "vip".to_string(),
+            status: TicketStatus::Valid,
+            created_at: Utc::now(),
+            checked_in_at: None,
+            checked_in_gate: None,
+            checked_in_by: None,
+            inside: false,
+            entry_count: 0,
+        }
+    }
+
+    #[test]
+    fn png_round_trips_payload() {
+        let payload = "ABCD2345EFGH6789";
+        assert_eq!(decode_png(&render_png(payload).unwrap()), payload);
+    }
+
+    #[test]
+    fn svg_round_trips_payload() {
+        let payload = "ABCD2345EFGH6789";
+        assert_eq!(decode_svg(&render_svg(payload).unwrap()), payload);
+    }
+
+    #[test]
+    fn signed_payload_decodes_and_verifies() {
+        let signer = signer();
+        let ticket = ticket();
+        let payload = ticket_qr_payload(&signer, &ticket);
+        assert_ne!(payload, ticket.code);
+
+        for decoded in [decode_png(&render_png(&payload).unwrap()), decode_svg(&render_svg(&payload).unwrap())] {
+            assert_eq!(decoded, payload);
+            let claims = signer.verify(&decoded).expect("decoded token should verify");
+            assert_eq!(claims.code, ticket.code);
+            assert_eq!(claims.ticket_id, ticket.id.unwrap().to_hex());
+            assert_eq!(claims.event_id, ticket.event_id.to_hex());
+        }
+    }
+
+    #[test]
+    fn tampered_payload_does_not_verify() {
+        let signer = signer();
+        let payload = ticket_qr_payload(&signer, &ticket());
+        let decoded = decode_png(&render_png(&payload).unwrap());
+        let tampered = decoded.replacen("v1.k2.", "v1.k1.", 1);
+        assert!(signer.verify(&tampered).is_err());
+    }
+
+    #[test]
+    fn unsigned_payload_is_ticket_code() {
+        let signer = TicketSigner::from_keys("", None).unwrap();
+        let ticket = ticket();
+        let payload = ticket_qr_payload(&signer, &ticket);
+        assert_eq!(payload, ticket.code);
+        assert_eq!(decode_png(&render_png(&payload).unwrap()), ticket.code);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rocket::request::{FromRequest, Outcome, Request};
+
+use crate::config::Config;
+
+// Di atas jumlah ini kunci yang sudah kosong dibuang supaya map tidak tumbuh terus
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Sliding window rate limiter di memori; batas berlaku per instance aplikasi.
+pub struct RateLimiter {
+    max_hits: usize,
+    window: Duration,
+    hits: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_hits: usize, window: Duration) -> Self {
+        RateLimiter { max_hits, window, hits: Mutex::new(HashMap::new()) }
+    }
+
+    /// Catat satu request untuk `key`; `false` jika batas dalam window sudah tercapai.
+    pub fn check(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap_or_else(|e| e.into_inner());
+        if hits.len() > PRUNE_THRESHOLD {
+            hits.retain(|_, times| times.back().is_some_and(|t| now.duration_since(*t) < self.window));
+        }
+
+        let times = hits.entry(key.to_string()).or_default();
+        while times.front().is_some_and(|t| now.duration_since(*t) >= self.window) {
+            times.pop_front();
+        }
+        if times.len() >= self.max_hits {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+/// Batas permintaan kirim ulang tiket oleh pembeli, per alamat email dan per IP.
+pub struct TicketResendLimits {
+    pub per_email: RateLimiter,
+    pub per_ip: RateLimiter,
+}
+
+impl TicketResendLimits {
+    pub fn from_config(config: &Config) -> Self {
+        let hour = Duration::from_secs(60 * 60);
+        TicketResendLimits {
+            per_email: RateLimiter::new(config.ticket_resend_email_limit, hour),
+            per_ip: RateLimiter::new(config.ticket_resend_ip_limit, hour),
+        }
+    }
+}
+
+/// IP klien untuk rate limit. Header IP hanya dipercaya jika `TRUSTED_IP_HEADER` diisi (aplikasi
+/// di belakang reverse proxy yang menimpa header tersebut); selain itu dipakai alamat socket,
+/// karena header dari klien bisa diganti-ganti untuk menghindari batas.
+pub struct ClientAddr(pub Option<IpAddr>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientAddr {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let trusted_header = request.rocket().state::<Config>().and_then(|c| c.trusted_ip_header.as_deref());
+        // Untuk daftar seperti X-Forwarded-For, entri terakhir adalah yang ditambahkan proxy sendiri
+        let from_header = trusted_header
+            .and_then(|name| request.headers().get_one(name))
+            .and_then(|value| value.split(',').next_back())
+            .and_then(|ip| ip.trim().parse().ok());
+        Outcome::Success(ClientAddr(from_header.or_else(|| request.remote().map(|addr| addr.ip()))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use super::*;
+
+    #[test]
+    fn limits_hits_within_window_per_key() {
+        let limiter = RateLimiter::new(2, Duration::from_millis(50));
+        assert!(limiter.check("a"));
+        assert!(limiter.check("a"));
+        assert!(!limiter.check("a"));
+        assert!(limiter.check("b"));
+
+        sleep(Duration::from_millis(60));
+        assert!(limiter.check("a"));
+    }
+
+    #[test]
+    fn prunes_expired_keys_past_threshold() {
+        let limiter = RateLimiter::new(1, Duration::from_millis(20));
+        for i in 0..=PRUNE_THRESHOLD {
+            assert!(limiter.check(&i.to_string()));
+        }
+        sleep(Duration::from_millis(30));
+        assert!(limiter.check("fresh"));
+        assert_eq!(limiter.hits.lock().unwrap().len(), 1);
+    }
+}
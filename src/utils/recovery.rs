@@ -0,0 +1,206 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId, Document}, options::{Collation, CollationStrength, FindOneOptions}, Collection, Database};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::{select, time::sleep};
+use rocket::{Orbit, Rocket, Shutdown};
+
+use crate::config::Config;
+use crate::models::email_template::EmailTemplateKind;
+use crate::models::{event::Event, order::{CheckoutRecoveryReport, Order}};
+use crate::utils::email_templates::{email_context, find_effective_template, render_email};
+use crate::utils::mailer::EmailMessage;
+use crate::utils::outbox::{EmailOutbox, OutboxSource};
+use crate::utils::time::stored_time;
+use crate::utils::wallet::WalletPasses;
+
+// Pembeli dengan order lunas untuk event yang sama tidak perlu diingatkan membayar
+async fn has_completed_order(db: &Database, order: &Order) -> mongodb::error::Result<bool> {
+    let collation = Collation::builder().locale("en").strength(CollationStrength::Secondary).build();
+    let options = FindOneOptions::builder().collation(collation).build();
+    let completed = db
+        .collection::<Order>("orders")
+        .find_one(
+            doc! {
+                "event_id": order.event_id,
+                "buyer_email": order.buyer_email.trim(),
+                "status": {"$in": ["paid", "sent"]},
+            },
+            options,
+        )
+        .await?;
+    Ok(completed.is_some())
+}
+
+/// Kirim template `payment_reminder` (dengan link pembayaran asli) ke order yang masih `pending`
+/// setelah `RECOVERY_DELAY_MINUTES`. Status pemulihan diklaim atomik sehingga tiap order
+/// paling banyak menerima satu email.
+pub async fn send_recovery_emails(db: &Database, config: &Config, wallet: &WalletPasses, outbox: &EmailOutbox) -> Result<usize> {
+    let now = Utc::now();
+    let cutoff = now - Duration::minutes(config.recovery_delay_minutes);
+    let collection: Collection<Order> = db.collection("orders");
+    let orders: Vec<Order> = collection
+        // `created_at` disimpan lewat `time::millis`, jadi batas waktu bisa dibandingkan sebagai string
+        .find(
+            doc! {
+                "status": "pending",
+                "recovery_status": null,
+                "payment_url": {"$ne": null},
+                "created_at": {"$lte": stored_time(cutoff)},
+            },
+            None,
+        )
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut queued = 0;
+    for order in orders.iter().filter(|o| o.created_at <= cutoff) {
+        let (Some(order_id), Some(payment_url)) = (order.id, order.payment_url.as_deref()) else {
+            continue;
+        };
+        let event = db.collection::<Event>("events").find_one(doc! {"_id": order.event_id}, None).await?;
+        let skip = match &event {
+            Some(event) => event.date <= now || has_completed_order(db, order).await?,
+            None => true,
+        };
+        let status = if skip { "skipped" } else { "sent" };
+
+        let claimed = collection
+            .update_one(
+                doc! {"_id": order_id, "status": "pending", "recovery_status": null},
+                doc! {"$set": {"recovery_status": status, "recovery_email_sent_at": if skip { None } else { Some(stored_time(now)) }}},
+                None,
+            )
+            .await?;
+        if skip || claimed.modified_count == 0 {
+            continue;
+        }
+        let Some(event) = event else { continue };
+
+        let template = find_effective_template(db, EmailTemplateKind::PaymentReminder, event.id).await?;
+        let context = email_context(config, wallet, &event, order, &[], "", Some(payment_url));
+        let rendered = match render_email(&template.subject, &template.html, &template.text, &context) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                println!("❌ Failed to render payment reminder for order {}: {:?}", order.midtrans_order_id, e);
+                continue;
+            }
+        };
+
+        let message = EmailMessage {
+            to: order.buyer_email.clone(),
+            subject: rendered.subject,
+            text: rendered.text,
+            html: Some(rendered.html),
+            attachments: Vec::new(),
+        };
+        let source = OutboxSource { order_id: Some(order_id), event_id: event.id, announcement_id: None };
+        outbox.enqueue(EmailTemplateKind::PaymentReminder.as_str(), source, message).await?;
+        queued += 1;
+    }
+    Ok(queued)
+}
+
+/// Catat konversi jika order yang baru lunas sebelumnya menerima email pemulihan.
+pub async fn mark_recovery_converted(db: &Database, order: &Order) {
+    let Some(order_id) = order.id else { return };
+    if let Err(e) = db
+        .collection::<Order>("orders")
+        .update_one(
+            doc! {"_id": order_id, "recovery_status": "sent"},
+            doc! {"$set": {"recovery_status": "converted", "recovered_at": stored_time(Utc::now())}},
+            None,
+        )
+        .await
+    {
+        eprintln!("Failed to mark checkout recovery for order {}: {:?}", order_id, e);
+    }
+}
+
+/// Rekap email pemulihan checkout, seluruhnya atau untuk satu event.
+pub async fn recovery_report(db: &Database, event_id: Option<ObjectId>) -> mongodb::error::Result<CheckoutRecoveryReport> {
+    let mut filter = doc! {"recovery_status": {"$ne": null}};
+    if let Some(event_id) = event_id {
+        filter.insert("event_id", event_id);
+    }
+    let pipeline = vec![
+        doc! { "$match": filter },
+        doc! { "$group": { "_id": "$recovery_status", "count": { "$sum": 1 }, "tickets": { "$sum": "$quantity" } } },
+    ];
+    let mut cursor = db.collection::<Order>("orders").aggregate(pipeline, None).await?;
+
+    let mut report = CheckoutRecoveryReport::default();
+    while let Some(group) = cursor.try_next().await? {
+        let count = group.get_i32("count").unwrap_or_default() as u32;
+        match group.get_str("_id").unwrap_or_default() {
+            "sent" => report.emails_sent += count,
+            "converted" => {
+                // Order yang terkonversi juga sudah menerima email
+                report.emails_sent += count;
+                report.converted = count;
+                report.recovered_tickets = group_tickets(&group);
+            }
+            "skipped" => report.skipped = count,
+            _ => {}
+        }
+    }
+    if report.emails_sent > 0 {
+        report.conversion_rate = (report.converted as f64 / report.emails_sent as f64 * 1000.0).round() / 10.0;
+    }
+    Ok(report)
+}
+
+fn group_tickets(group: &Document) -> u32 {
+    group
+        .get_i32("tickets")
+        .map(|t| t as u32)
+        .or_else(|_| group.get_i64("tickets").map(|t| t as u32))
+        .unwrap_or_default()
+}
+
+async fn run(db: Database, config: Config, wallet: WalletPasses, outbox: EmailOutbox, mut shutdown: Shutdown) {
+    let interval = std::time::Duration::from_secs(config.recovery_poll_secs.max(1));
+    loop {
+        match send_recovery_emails(&db, &config, &wallet, &outbox).await {
+            Ok(0) => {}
+            Ok(queued) => println!("💳 Queued {} checkout recovery email(s)", queued),
+            Err(e) => println!("❌ Checkout recovery error: {:?}", e),
+        }
+
+        select! {
+            _ = sleep(interval) => {},
+            _ = &mut shutdown => break,
+        }
+    }
+}
+
+/// Penjadwal email pemulihan checkout yang berjalan di dalam proses Rocket.
+pub struct CheckoutRecoveryScheduler;
+
+#[rocket::async_trait]
+impl Fairing for CheckoutRecoveryScheduler {
+    fn info(&self) -> Info {
+        Info {
+            name: "Checkout recovery scheduler",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let (Some(db), Some(config), Some(wallet), Some(outbox)) = (
+            rocket.state::<Database>(),
+            rocket.state::<Config>(),
+            rocket.state::<WalletPasses>(),
+            rocket.state::<EmailOutbox>(),
+        ) else {
+            return;
+        };
+        if config.recovery_delay_minutes <= 0 {
+            println!("💳 RECOVERY_DELAY_MINUTES is 0; checkout recovery emails disabled");
+            return;
+        }
+        rocket::tokio::spawn(run(db.clone(), config.clone(), wallet.clone(), outbox.clone(), rocket.shutdown()));
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId}, Collection, Database};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::tokio::{select, time::sleep};
+use rocket::{Orbit, Rocket, Shutdown};
+
+use crate::config::Config;
+use crate::db::is_duplicate_key;
+use crate::models::email_template::EmailTemplateKind;
+use crate::models::reminder::ReminderLog;
+use crate::models::{event::Event, order::Order, ticket::Ticket};
+use crate::utils::email_templates::{email_context, find_effective_template, render_email};
+use crate::utils::mailer::EmailMessage;
+use crate::utils::notifier::{event_reminder_text, Notifier};
+use crate::utils::outbox::{EmailOutbox, OutboxSource};
+use crate::utils::tickets::find_tickets;
+use crate::utils::time::stored_time;
+use crate::utils::wallet::WalletPasses;
+
+fn offset_label(hours: u32) -> String {
+    if hours.is_multiple_of(24) { format!("{}d", hours / 24) } else { format!("{}h", hours) }
+}
+
+// Dipakai sebagai `message` di template, mis. "dalam 7 hari"
+fn offset_phrase(hours: u32) -> String {
+    if hours.is_multiple_of(24) { format!("dalam {} hari", hours / 24) } else { format!("dalam {} jam", hours) }
+}
+
+// Untuk pengingat yang terlambat: frasa dari sisa waktu sebenarnya, bukan dari offset
+fn remaining_phrase(remaining: Duration) -> String {
+    match remaining.num_hours() {
+        h if h >= 24 => format!("dalam {} hari", h / 24),
+        h if h >= 1 => format!("dalam {} jam", h),
+        _ => "dalam kurang dari 1 jam".to_string(),
+    }
+}
+
+/// Antrekan pengingat yang jatuh tempo untuk semua event mendatang. Setiap pengingat dicatat di
+/// `reminder_log` sebelum diantrekan, jadi restart atau worker ganda tidak mengirimnya dua kali.
+pub async fn send_due_reminders(
+    db: &Database,
+    config: &Config,
+    wallet: &WalletPasses,
+    outbox: &EmailOutbox,
+    notifier: &Notifier,
+) -> Result<usize> {
+    let Some(&max_hours) = config.reminder_offset_hours.iter().max() else {
+        return Ok(0);
+    };
+    let now = Utc::now();
+    let poll_window = Duration::seconds(config.reminder_poll_secs.max(1) as i64);
+    // `date` disimpan lewat `time::millis`, jadi rentang string sama dengan rentang waktu
+    let window = doc! {
+        "$gt": stored_time(now),
+        "$lte": stored_time(now + Duration::hours(max_hours as i64)),
+    };
+    let events: Vec<Event> = db
+        .collection::<Event>("events")
+        .find(doc! {"status": {"$ne": "draft"}, "date": window}, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let logs: Collection<ReminderLog> = db.collection("reminder_log");
+    let mut queued = 0;
+    for event in events.iter().filter(|e| e.date > now && e.date - Duration::hours(max_hours as i64) <= now) {
+        let Some(event_id) = event.id else { continue };
+        // Hanya offset terkecil yang sudah jatuh tempo yang dikirim, jadi offset berikutnya belum jatuh tempo.
+        // Pengingat yang terlewat (mis. server mati atau event dibuat mepet) tidak menyusul satu per satu
+        let Some(hours) = config
+            .reminder_offset_hours
+            .iter()
+            .copied()
+            .filter(|h| event.date - Duration::hours(*h as i64) <= now)
+            .min()
+        else {
+            continue;
+        };
+        let offset = offset_label(hours);
+        let due_at = event.date - Duration::hours(hours as i64);
+        // Terlambat lebih dari satu siklus polling: "dalam 7 hari" untuk event 2 hari lagi itu menyesatkan
+        let phrase = if now - due_at <= poll_window {
+            offset_phrase(hours)
+        } else {
+            remaining_phrase(event.date - now)
+        };
+
+        let reminded: HashSet<_> = logs
+            .find(doc! {"event_id": event_id, "offset": &offset}, None)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .map(|log| log.order_id)
+            .collect();
+        let orders: Vec<Order> = db
+            .collection::<Order>("orders")
+            .find(doc! {"event_id": event_id, "status": {"$in": ["paid", "sent"]}}, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        // Pembeli yang baru membeli setelah jadwal pengingat tidak perlu diingatkan lagi
+        let due_orders: Vec<(ObjectId, &Order)> = orders
+            .iter()
+            .filter(|o| o.created_at <= due_at)
+            .filter_map(|o| o.id.map(|id| (id, o)))
+            .filter(|(id, _)| !reminded.contains(id))
+            .collect();
+        if due_orders.is_empty() {
+            continue;
+        }
+        let order_ids: Vec<ObjectId> = due_orders.iter().map(|(id, _)| *id).collect();
+        let mut tickets_by_order: HashMap<ObjectId, Vec<Ticket>> = HashMap::new();
+        for ticket in find_tickets(db, doc! {"order_id": {"$in": &order_ids}, "status": {"$ne": "void"}}).await? {
+            tickets_by_order.entry(ticket.order_id).or_default().push(ticket);
+        }
+
+        let template = find_effective_template(db, EmailTemplateKind::EventReminder, Some(event_id)).await?;
+        for (order_id, order) in due_orders {
+            // Order yang tiketnya belum diterbitkan (pengiriman manual) diingatkan setelah tiket terbit
+            let Some(tickets) = tickets_by_order.get(&order_id) else { continue };
+            let active_tickets: Vec<&Ticket> = tickets.iter().collect();
+
+            let log = ReminderLog {
+                id: format!("{}:{}", order_id.to_hex(), offset),
+                order_id,
+                event_id,
+                offset: offset.clone(),
+                outbox_id: None,
+                created_at: Utc::now(),
+            };
+            match logs.insert_one(&log, None).await {
+                Ok(_) => {}
+                Err(e) if is_duplicate_key(&e) => continue,
+                Err(e) => return Err(e.into()),
+            }
+
+            let context = email_context(config, wallet, event, order, &active_tickets, &phrase, None);
+            let rendered = match render_email(&template.subject, &template.html, &template.text, &context) {
+                Ok(rendered) => rendered,
+                Err(e) => {
+                    println!("❌ Failed to render reminder for order {}: {:?}", order.midtrans_order_id, e);
+                    continue;
+                }
+            };
+
+            let message = EmailMessage {
+                to: order.buyer_email.clone(),
+                subject: rendered.subject,
+                text: rendered.text,
+                html: Some(rendered.html),
+                attachments: Vec::new(),
+            };
+            let source = OutboxSource { order_id: Some(order_id), event_id: Some(event_id), announcement_id: None };
+            let outbox_id = outbox.enqueue(EmailTemplateKind::EventReminder.as_str(), source, message).await?;
+            logs.update_one(doc! {"_id": &log.id}, doc! {"$set": {"outbox_id": outbox_id}}, None).await?;
+            notifier.notify(order, event_reminder_text(event, &phrase));
+            queued += 1;
+        }
+    }
+    Ok(queued)
+}
+
+async fn run(db: Database, config: Config, wallet: WalletPasses, outbox: EmailOutbox, notifier: Notifier, mut shutdown: Shutdown) {
+    let interval = std::time::Duration::from_secs(config.reminder_poll_secs.max(1));
+    loop {
+        match send_due_reminders(&db, &config, &wallet, &outbox, &notifier).await {
+            Ok(0) => {}
+            Ok(queued) => println!("⏰ Queued {} event reminder(s)", queued),
+            Err(e) => println!("❌ Event reminder error: {:?}", e),
+        }
+
+        select! {
+            _ = sleep(interval) => {},
+            _ = &mut shutdown => break,
+        }
+    }
+}
+
+/// Penjadwal pengingat event yang berjalan di dalam proses Rocket.
+pub struct ReminderScheduler;
+
+#[rocket::async_trait]
+impl Fairing for ReminderScheduler {
+    fn info(&self) -> Info {
+        Info {
+            name: "Event reminder scheduler",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let (Some(db), Some(config), Some(wallet), Some(outbox), Some(notifier)) = (
+            rocket.state::<Database>(),
+            rocket.state::<Config>(),
+            rocket.state::<WalletPasses>(),
+            rocket.state::<EmailOutbox>(),
+            rocket.state::<Notifier>(),
+        ) else {
+            return;
+        };
+        if config.reminder_offset_hours.is_empty() {
+            println!("⏰ REMINDER_OFFSETS empty; event reminders disabled");
+            return;
+        }
+        rocket::tokio::spawn(run(db.clone(), config.clone(), wallet.clone(), outbox.clone(), notifier.clone(), rocket.shutdown()));
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine as _};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::models::ticket::Ticket;
+
+const TOKEN_VERSION: &str = "v1";
+
+/// Isi token tiket yang ditandatangani; field dibuat pendek agar QR tetap kecil.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketClaims {
+    #[serde(rename = "t")]
+    pub ticket_id: String,
+    #[serde(rename = "e")]
+    pub event_id: String,
+    #[serde(rename = "r")]
+    pub tier: String,
+    #[serde(rename = "c")]
+    pub code: String,
+    #[serde(rename = "i")]
+    pub issued_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicSigningKey {
+    pub kid: String,
+    pub alg: &'static str,
+    pub public_key: String,
+}
+
+#[derive(Clone)]
+struct TicketKey {
+    kid: String,
+    signing_key: SigningKey,
+}
+
+/// Penandatangan token tiket Ed25519 dengan dukungan rotasi kunci via `kid`.
+/// Semua kunci di `TICKET_SIGNING_KEYS` diterima saat verifikasi, tetapi hanya
+/// kunci aktif yang dipakai untuk menandatangani token baru.
+#[derive(Clone)]
+pub struct TicketSigner {
+    keys: Vec<TicketKey>,
+    active_kid: Option<String>,
+}
+
+impl TicketSigner {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::from_keys(&config.ticket_signing_keys, config.ticket_signing_key_id.as_deref())
+    }
+
+    /// `keys` berformat `<kid>:<base64 seed>,...`; tanpa `active_kid` kunci terakhir yang aktif.
+    pub fn from_keys(keys_spec: &str, active_kid: Option<&str>) -> Result<Self> {
+        let mut keys = Vec::new();
+        for entry in keys_spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (kid, seed) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("TICKET_SIGNING_KEYS entry must be <kid>:<base64 seed>"))?;
+            let seed: [u8; 32] = STANDARD
+                .decode(seed.trim())?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Signing key {} must be a 32-byte seed", kid))?;
+            keys.push(TicketKey {
+                kid: kid.trim().to_string(),
+                signing_key: SigningKey::from_bytes(&seed),
+            });
+        }
+
+        let active_kid = match active_kid {
+            Some(kid) if keys.iter().any(|k| k.kid == kid) => Some(kid.to_string()),
+            Some(kid) => return Err(anyhow::anyhow!("TICKET_SIGNING_KEY_ID {} is not configured", kid)),
+            None => keys.last().map(|k| k.kid.clone()),
+        };
+
+        Ok(TicketSigner { keys, active_kid })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.active_kid.is_some()
+    }
+
+    pub fn active_kid(&self) -> Option<&str> {
+        self.active_kid.as_deref()
+    }
+
+    pub fn public_keys(&self) -> Vec<PublicSigningKey> {
+        self.keys
+            .iter()
+            .map(|k| PublicSigningKey {
+                kid: k.kid.clone(),
+                alg: "Ed25519",
+                public_key: URL_SAFE_NO_PAD.encode(k.signing_key.verifying_key().as_bytes()),
+            })
+            .collect()
+    }
+
+    /// Format token: `v1.<kid>.<payload base64url>.<signature base64url>`.
+    pub fn sign(&self, claims: &TicketClaims) -> Result<String> {
+        let kid = self
+            .active_kid
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Ticket signing is not configured"))?;
+        let key = self.key(kid).ok_or_else(|| anyhow::anyhow!("Unknown signing key {}", kid))?;
+
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?);
+        let signing_input = format!("{}.{}.{}", TOKEN_VERSION, kid, payload);
+        let signature = key.signing_key.sign(signing_input.as_bytes());
+        Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature.to_bytes())))
+    }
+
+    pub fn verify(&self, token: &str) -> Result<TicketClaims> {
+        let parts: Vec<&str> = token.trim().split('.').collect();
+        let [version, kid, payload, signature] = parts[..] else {
+            return Err(anyhow::anyhow!("Malformed ticket token"));
+        };
+        if version != TOKEN_VERSION {
+            return Err(anyhow::anyhow!("Unsupported ticket token version"));
+        }
+
+        let key = self.key(kid).ok_or_else(|| anyhow::anyhow!("Unknown signing key {}", kid))?;
+        let verifying_key: VerifyingKey = key.signing_key.verifying_key();
+        let signature = Signature::from_slice(&URL_SAFE_NO_PAD.decode(signature)?)?;
+        let signing_input = format!("{}.{}.{}", version, kid, payload);
+        verifying_key.verify(signing_input.as_bytes(), &signature)?;
+
+        Ok(serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload)?)?)
+    }
+
+    fn key(&self, kid: &str) -> Option<&TicketKey> {
+        self.keys.iter().find(|k| k.kid == kid)
+    }
+}
+
+impl TicketClaims {
+    pub fn for_ticket(ticket: &Ticket) -> Self {
+        TicketClaims {
+            ticket_id: ticket.id.map(|id| id.to_hex()).unwrap_or_default(),
+            event_id: ticket.event_id.to_hex(),
+            tier: ticket.tier.clone(),
+            code: ticket.code.clone(),
+            issued_at: ticket.created_at.timestamp(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_spec(entries: &[(&str, u8)]) -> String {
+        entries
+            .iter()
+            .map(|(kid, seed)| format!("{}:{}", kid, STANDARD.encode([*seed; 32])))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn claims() -> TicketClaims {
+        TicketClaims {
+            ticket_id: "65f1a2b3c4d5e6f7a8b9c0d2".to_string(),
+            event_id: "65f1a2b3c4d5e6f7a8b9c0d1".to_string(),
+            tier: "vip".to_string(),
+            code: "TCK-ABC123".to_string(),
+            issued_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn tokens_from_previous_kid_verify_after_rotation() {
+        let before = TicketSigner::from_keys(&key_spec(&[("2024-01", 1)]), None).unwrap();
+        let old_token = before.sign(&claims()).unwrap();
+        assert!(old_token.starts_with("v1.2024-01."));
+
+        // Kunci baru ditambahkan di akhir daftar dan otomatis menjadi kunci aktif
+        let after = TicketSigner::from_keys(&key_spec(&[("2024-01", 1), ("2024-07", 2)]), None).unwrap();
+        assert_eq!(after.active_kid(), Some("2024-07"));
+        assert_eq!(after.verify(&old_token).unwrap().code, "TCK-ABC123");
+
+        let new_token = after.sign(&claims()).unwrap();
+        assert!(new_token.starts_with("v1.2024-07."));
+        assert_eq!(after.verify(&new_token).unwrap().tier, "vip");
+        assert!(before.verify(&new_token).is_err());
+    }
+
+    #[test]
+    fn unknown_or_retired_kids_are_rejected() {
+        let rotated = TicketSigner::from_keys(&key_spec(&[("2024-01", 1), ("2024-07", 2)]), Some("2024-01")).unwrap();
+        let token = rotated.sign(&claims()).unwrap();
+
+        let retired = TicketSigner::from_keys(&key_spec(&[("2024-07", 2)]), None).unwrap();
+        assert!(retired.verify(&token).is_err());
+
+        let unknown_kid = token.replacen(".2024-01.", ".2099-01.", 1);
+        assert!(rotated.verify(&unknown_kid).is_err());
+
+        // Tanda tangan kunci lama tidak berlaku jika kid diganti ke kunci lain yang dikenal
+        let swapped_kid = token.replacen(".2024-01.", ".2024-07.", 1);
+        assert!(rotated.verify(&swapped_kid).is_err());
+
+        assert!(TicketSigner::from_keys(&key_spec(&[("2024-07", 2)]), Some("2024-01")).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use mongodb::{bson::doc, Collection};
+
+use crate::models::event::Event;
+
+/// Slug dasar dari `source`. Slug berbentuk 24 karakter hex diberi prefix karena
+/// `get_event` mencoba ObjectId lebih dulu, sehingga slug seperti itu tidak akan pernah terjangkau.
+pub fn slug_base(source: &str) -> String {
+    match ::slug::slugify(source) {
+        s if s.is_empty() => "event".to_string(),
+        s if s.len() == 24 && s.chars().all(|c| c.is_ascii_hexdigit()) => format!("event-{}", s),
+        s => s,
+    }
+}
+
+/// Slug pertama yang belum dipakai. Hanya tebakan awal: keunikan sebenarnya dijamin
+/// oleh unique index `events.slug`, jadi pemanggil tetap harus retry saat E11000.
+pub async fn unique_event_slug(
+    collection: &Collection<Event>,
+    source: &str,
+) -> Result<String, mongodb::error::Error> {
+    let base = slug_base(source);
+
+    let mut candidate = base.clone();
+    let mut suffix = 2;
+    while collection.count_documents(doc! {"slug": &candidate}, None).await? > 0 {
+        candidate = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    Ok(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::slug_base;
+
+    #[test]
+    fn object_id_like_slugs_are_prefixed() {
+        assert_eq!(slug_base("65f1a2b3c4d5e6f7a8b9c0d1"), "event-65f1a2b3c4d5e6f7a8b9c0d1");
+        assert_eq!(slug_base("Konser Musim Panas"), "konser-musim-panas");
+        assert_eq!(slug_base("!!!"), "event");
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+use rocket::tokio::fs;
+
+use crate::config::Config;
+
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    /// Simpan file dengan key tertentu dan kembalikan URL publiknya.
+    async fn put(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<String>;
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+pub struct LocalStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, base_url: impl Into<String>) -> Self {
+        LocalStorage {
+            root: root.into(),
+            base_url: base_url.into(),
+        }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(anyhow::anyhow!("Invalid storage key: {}", key));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: &[u8], _content_type: &str) -> Result<String> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&path, bytes).await?;
+        Ok(format!("{}/{}", self.base_url, key))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+pub fn storage_from_config(config: &Config) -> Box<dyn Storage> {
+    match config.storage_backend.as_str() {
+        "local" => Box::new(LocalStorage::new(
+            &config.media_dir,
+            format!("{}/media", config.public_base_url),
+        )),
+        other => panic!("Unsupported STORAGE_BACKEND: {}", other),
+    }
+}
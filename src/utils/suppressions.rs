@@ -0,0 +1,59 @@
+use chrono::Utc;
+use futures::TryStreamExt;
+use mongodb::{bson::{self, doc}, options::{FindOptions, UpdateOptions}, Collection, Database};
+
+use crate::models::email_suppression::{EmailSuppression, SuppressionReason};
+
+fn collection(db: &Database) -> Collection<EmailSuppression> {
+    db.collection("email_suppressions")
+}
+
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+pub async fn is_suppressed(db: &Database, email: &str) -> mongodb::error::Result<bool> {
+    let found = collection(db)
+        .find_one(doc! {"email": normalize_email(email)}, None)
+        .await?;
+    Ok(found.is_some())
+}
+
+/// Tambahkan alamat ke daftar suppression; alamat yang sudah ada tidak diubah.
+pub async fn suppress_email(
+    db: &Database,
+    email: &str,
+    reason: SuppressionReason,
+    detail: Option<&str>,
+    provider_message_id: Option<&str>,
+) -> mongodb::error::Result<()> {
+    let suppression = EmailSuppression {
+        id: None,
+        email: normalize_email(email),
+        reason,
+        detail: detail.map(str::to_string),
+        provider_message_id: provider_message_id.map(str::to_string),
+        created_at: Utc::now(),
+    };
+    let options = UpdateOptions::builder().upsert(true).build();
+    collection(db)
+        .update_one(
+            doc! {"email": &suppression.email},
+            doc! {"$setOnInsert": bson::to_document(&suppression)?},
+            options,
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn find_suppressions(db: &Database) -> mongodb::error::Result<Vec<EmailSuppression>> {
+    let find_options = FindOptions::builder().sort(doc! {"created_at": -1}).build();
+    collection(db).find(doc! {}, find_options).await?.try_collect().await
+}
+
+pub async fn remove_suppression(db: &Database, email: &str) -> mongodb::error::Result<bool> {
+    let result = collection(db)
+        .delete_one(doc! {"email": normalize_email(email)}, None)
+        .await?;
+    Ok(result.deleted_count > 0)
+}
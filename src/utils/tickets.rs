@@ -0,0 +1,170 @@
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId, Document}, options::FindOptions, Collection, Database};
+use rand::Rng;
+
+use crate::config::Config;
+use crate::db::is_duplicate_key;
+use crate::models::{event::Event, order::Order, ticket::{Ticket, TicketStatus}};
+use crate::utils::signing::{TicketClaims, TicketSigner};
+
+// Tanpa huruf/angka yang mirip (0/O, 1/I) supaya mudah dibaca manual
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 16;
+const ACCESS_TOKEN_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const ACCESS_TOKEN_LENGTH: usize = 32;
+
+fn random_string(alphabet: &[u8], length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
+
+pub fn generate_ticket_code() -> String {
+    random_string(CODE_ALPHABET, CODE_LENGTH)
+}
+
+pub fn generate_access_token() -> String {
+    random_string(ACCESS_TOKEN_ALPHABET, ACCESS_TOKEN_LENGTH)
+}
+
+/// Isi QR code tiket yang dipindai di pintu masuk: token bertanda tangan
+/// jika kunci sudah dikonfigurasi, selain itu kode tiket saja.
+pub fn ticket_qr_payload(signer: &TicketSigner, ticket: &Ticket) -> String {
+    if signer.is_enabled() {
+        match signer.sign(&TicketClaims::for_ticket(ticket)) {
+            Ok(token) => return token,
+            Err(e) => eprintln!("Failed to sign ticket {}: {:?}", ticket.code, e),
+        }
+    }
+    ticket.code.clone()
+}
+
+pub fn ticket_qr_url(config: &Config, ticket: &Ticket, format: &str) -> String {
+    ticket_resource_url(config, ticket, &format!("qr.{}", format))
+}
+
+/// URL publik sumber daya tiket (QR, PDF, wallet) lengkap dengan token akses.
+pub fn ticket_resource_url(config: &Config, ticket: &Ticket, resource: &str) -> String {
+    format!(
+        "{}/api/tickets/{}/{}?token={}",
+        config.public_base_url,
+        ticket.id.map(|id| id.to_hex()).unwrap_or_default(),
+        resource,
+        ticket.access_token
+    )
+}
+
+/// Ambil tiket untuk endpoint publik; token akses wajib cocok.
+pub async fn find_ticket_with_token(
+    db: &Database,
+    id: &str,
+    token: &str,
+) -> Result<Option<Ticket>, mongodb::error::Error> {
+    let Ok(object_id) = ObjectId::parse_str(id) else {
+        return Ok(None);
+    };
+    if token.is_empty() {
+        return Ok(None);
+    }
+
+    let collection: Collection<Ticket> = db.collection("tickets");
+    collection
+        .find_one(doc! {"_id": object_id, "access_token": token}, None)
+        .await
+}
+
+pub async fn find_tickets(db: &Database, filter: Document) -> Result<Vec<Ticket>, mongodb::error::Error> {
+    let collection: Collection<Ticket> = db.collection("tickets");
+    let find_options = FindOptions::builder().sort(doc! {"created_at": 1, "_id": 1}).build();
+    let mut cursor = collection.find(filter, find_options).await?;
+
+    let mut tickets = Vec::new();
+    while let Some(ticket) = cursor.try_next().await? {
+        tickets.push(ticket);
+    }
+    Ok(tickets)
+}
+
+/// Terbitkan satu tiket per quantity untuk order yang sudah dibayar.
+/// Aman dipanggil berulang maupun bersamaan: index unik `(order_id, seq)` menolak penerbitan
+/// kedua, dan tiket yang sudah ada dikembalikan apa adanya.
+pub async fn issue_tickets_for_order(db: &Database, order: &Order) -> Result<Vec<Ticket>, mongodb::error::Error> {
+    let Some(order_id) = order.id else {
+        return Ok(Vec::new());
+    };
+
+    let existing = find_tickets(db, doc! {"order_id": order_id}).await?;
+    if !existing.is_empty() {
+        return Ok(existing);
+    }
+
+    let now = chrono::Utc::now();
+    let mut tickets: Vec<Ticket> = (0..order.quantity.max(1))
+        .map(|seq| Ticket {
+            id: None,
+            order_id,
+            event_id: order.event_id,
+            seq: seq as u32,
+            code: generate_ticket_code(),
+            access_token: generate_access_token(),
+            attendee_name: order.buyer_name.clone(),
+            tier: order.tier.clone(),
+            status: TicketStatus::Valid,
+            created_at: now,
+            checked_in_at: None,
+            checked_in_gate: None,
+            checked_in_by: None,
+            inside: false,
+            entry_count: 0,
+        })
+        .collect();
+
+    let collection: Collection<Ticket> = db.collection("tickets");
+    let result = match collection.insert_many(&tickets, None).await {
+        Ok(result) => result,
+        Err(e) if is_duplicate_key(&e) => return find_tickets(db, doc! {"order_id": order_id}).await,
+        Err(e) => return Err(e),
+    };
+    for (index, ticket) in tickets.iter_mut().enumerate() {
+        ticket.id = result.inserted_ids.get(&index).and_then(|id| id.as_object_id());
+    }
+    Ok(tickets)
+}
+
+/// Kurangi `available_tickets` secara atomik; `false` jika sisa tiket tidak cukup.
+pub async fn reserve_event_tickets(db: &Database, event_id: ObjectId, quantity: i32) -> Result<bool, mongodb::error::Error> {
+    let reserved = db
+        .collection::<Event>("events")
+        .find_one_and_update(
+            doc! {"_id": event_id, "available_tickets": {"$gte": quantity}},
+            doc! {"$inc": {"available_tickets": -quantity}},
+            None,
+        )
+        .await?;
+    Ok(reserved.is_some())
+}
+
+/// Kembalikan kuota tiket order yang gagal, kedaluwarsa, atau dibatalkan.
+pub async fn release_event_tickets(db: &Database, event_id: ObjectId, quantity: i32) {
+    if let Err(e) = db
+        .collection::<Event>("events")
+        .update_one(doc! {"_id": event_id}, doc! {"$inc": {"available_tickets": quantity}}, None)
+        .await
+    {
+        eprintln!("Failed to release {} ticket(s) for event {}: {:?}", quantity, event_id, e);
+    }
+}
+
+/// Void semua tiket order (mis. setelah refund); pemegang tiket tidak lagi dihitung di dalam venue.
+pub async fn void_order_tickets(db: &Database, order_id: ObjectId) -> Result<u64, mongodb::error::Error> {
+    let result = db
+        .collection::<Ticket>("tickets")
+        .update_many(
+            doc! {"order_id": order_id, "status": {"$ne": "void"}},
+            doc! {"$set": {"status": "void", "inside": false}},
+            None,
+        )
+        .await?;
+    Ok(result.modified_count)
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Format timestamp yang disimpan sebagai string RFC3339 dengan presisi milidetik tetap.
+/// Jumlah digit pecahan yang berubah-ubah (format bawaan serde chrono) membuat urutan string
+/// tidak lagi sama dengan urutan waktu, jadi field yang diurutkan atau dipakai sebagai filter
+/// memakai format ini, baik lewat `#[serde(with)]` maupun `doc!`.
+pub fn stored_time(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// `#[serde(with = "crate::utils::time::millis")]` untuk `DateTime<Utc>`.
+pub mod millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&stored_time(*date))
+    }
+
+    // Data lama dengan format apa pun yang valid RFC3339 tetap terbaca
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        DateTime::<Utc>::deserialize(deserializer)
+    }
+}
+
+/// Sama dengan `millis` untuk `Option<DateTime<Utc>>`.
+pub mod option_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(date: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.serialize_some(&stored_time(*date)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<DateTime<Utc>>::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::stored_time;
+
+    #[test]
+    fn stored_times_sort_chronologically() {
+        let whole = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+        let later = whole + chrono::Duration::microseconds(1500);
+        assert_eq!(stored_time(whole), "2025-01-01T10:00:00.000Z");
+        assert_eq!(stored_time(later), "2025-01-01T10:00:00.001Z");
+        assert!(stored_time(whole) < stored_time(later));
+    }
+}
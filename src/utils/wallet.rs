@@ -0,0 +1,406 @@
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+
+use anyhow::{Context, Result};
+use image::{ImageBuffer, ImageOutputFormat, Rgb};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use openssl::pkcs7::{Pkcs7, Pkcs7Flags};
+use openssl::pkey::{PKey, Private};
+use openssl::stack::Stack;
+use openssl::x509::X509;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::config::Config;
+use crate::models::{event::Event, ticket::Ticket};
+use crate::utils::tickets::ticket_resource_url;
+
+const GOOGLE_SAVE_URL: &str = "https://pay.google.com/gp/v/save";
+const ICON_SIZES: [(&str, u32); 3] = [("icon.png", 29), ("icon@2x.png", 58), ("icon@3x.png", 87)];
+
+/// Sertifikat Pass Type ID untuk menandatangani bundle `.pkpass`.
+#[derive(Clone)]
+pub struct ApplePassSigner {
+    pass_type_id: String,
+    team_id: String,
+    certificate: X509,
+    key: PKey<Private>,
+    wwdr: Option<X509>,
+}
+
+/// Service account Google Wallet untuk membuat link "Save to Google Wallet".
+#[derive(Clone)]
+pub struct GoogleWalletIssuer {
+    issuer_id: String,
+    client_email: String,
+    key: EncodingKey,
+}
+
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+}
+
+#[derive(Clone)]
+pub struct WalletPasses {
+    pub organization_name: String,
+    pub apple: Option<ApplePassSigner>,
+    pub google: Option<GoogleWalletIssuer>,
+}
+
+impl WalletPasses {
+    /// Masing-masing wallet aktif hanya jika seluruh konfigurasinya tersedia.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let apple = match (
+            &config.apple_pass_type_id,
+            &config.apple_team_id,
+            &config.apple_pass_cert_path,
+            &config.apple_pass_key_path,
+        ) {
+            (Some(pass_type_id), Some(team_id), Some(cert_path), Some(key_path)) => {
+                let certificate = X509::from_pem(&std::fs::read(cert_path).context("Failed to read APPLE_PASS_CERT")?)?;
+                let key_pem = std::fs::read(key_path).context("Failed to read APPLE_PASS_KEY")?;
+                let key = match &config.apple_pass_key_password {
+                    Some(password) => PKey::private_key_from_pem_passphrase(&key_pem, password.as_bytes())?,
+                    None => PKey::private_key_from_pem(&key_pem)?,
+                };
+                let wwdr = match &config.apple_wwdr_cert_path {
+                    Some(path) => Some(X509::from_pem(&std::fs::read(path).context("Failed to read APPLE_WWDR_CERT")?)?),
+                    None => None,
+                };
+                Some(ApplePassSigner {
+                    pass_type_id: pass_type_id.clone(),
+                    team_id: team_id.clone(),
+                    certificate,
+                    key,
+                    wwdr,
+                })
+            }
+            _ => None,
+        };
+
+        let google = match (&config.google_wallet_issuer_id, &config.google_wallet_service_account_path) {
+            (Some(issuer_id), Some(path)) => {
+                let raw = std::fs::read(path).context("Failed to read GOOGLE_WALLET_SERVICE_ACCOUNT")?;
+                let account: ServiceAccountKey = serde_json::from_slice(&raw)?;
+                Some(GoogleWalletIssuer {
+                    issuer_id: issuer_id.clone(),
+                    client_email: account.client_email,
+                    key: EncodingKey::from_rsa_pem(account.private_key.as_bytes())?,
+                })
+            }
+            _ => None,
+        };
+
+        Ok(WalletPasses {
+            organization_name: config.organization_name.clone(),
+            apple,
+            google,
+        })
+    }
+}
+
+/// Link unduhan wallet di halaman tiket & email; kosong jika wallet belum dikonfigurasi.
+#[derive(Debug, Serialize)]
+pub struct WalletLinks {
+    pub apple_wallet_url: Option<String>,
+    pub google_wallet_url: Option<String>,
+}
+
+pub fn wallet_links(config: &Config, wallet: &WalletPasses, ticket: &Ticket) -> WalletLinks {
+    WalletLinks {
+        apple_wallet_url: wallet.apple.as_ref().map(|_| ticket_resource_url(config, ticket, "wallet.pkpass")),
+        google_wallet_url: wallet.google.as_ref().map(|_| ticket_resource_url(config, ticket, "wallet/google")),
+    }
+}
+
+fn accent_rgb(event: &Event) -> (u8, u8, u8) {
+    let (r, g, b) = event.ticket_layout.accent_rgb().unwrap_or((0.12, 0.23, 0.54));
+    ((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+fn render_icon(size: u32, color: (u8, u8, u8)) -> Result<Vec<u8>> {
+    let image = ImageBuffer::from_pixel(size, size, Rgb([color.0, color.1, color.2]));
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+    Ok(png)
+}
+
+fn pass_json(signer: &ApplePassSigner, organization_name: &str, event: &Event, ticket: &Ticket, qr_payload: &str) -> serde_json::Value {
+    let (r, g, b) = accent_rgb(event);
+    let local_date = event.local_date();
+
+    json!({
+        "formatVersion": 1,
+        "passTypeIdentifier": signer.pass_type_id,
+        "teamIdentifier": signer.team_id,
+        "serialNumber": ticket.code,
+        "organizationName": organization_name,
+        "description": format!("Tiket {}", event.name),
+        "logoText": event.name,
+        "foregroundColor": "rgb(255, 255, 255)",
+        "labelColor": "rgb(255, 255, 255)",
+        "backgroundColor": format!("rgb({}, {}, {})", r, g, b),
+        "relevantDate": event.date.to_rfc3339(),
+        "barcodes": [{
+            "format": "PKBarcodeFormatQR",
+            "message": qr_payload,
+            "messageEncoding": "iso-8859-1",
+            "altText": ticket.code,
+        }],
+        "eventTicket": {
+            "primaryFields": [{"key": "event", "label": "EVENT", "value": event.name}],
+            "secondaryFields": [
+                {"key": "date", "label": "TANGGAL", "value": local_date.format("%d %b %Y, %H:%M").to_string()},
+                {"key": "tier", "label": "TIER", "value": ticket.tier},
+            ],
+            "auxiliaryFields": [{"key": "location", "label": "LOKASI", "value": event.location}],
+            "backFields": [
+                {"key": "holder", "label": "Nama", "value": ticket.attendee_name},
+                {"key": "code", "label": "Kode Tiket", "value": ticket.code},
+                {"key": "timezone", "label": "Zona Waktu", "value": event.timezone},
+            ],
+        },
+    })
+}
+
+/// Bundle `.pkpass`: pass.json + ikon, manifest SHA-1 tiap file, dan tanda tangan
+/// PKCS#7 detached atas manifest.
+pub fn build_pkpass(wallet: &WalletPasses, event: &Event, ticket: &Ticket, qr_payload: &str) -> Result<Vec<u8>> {
+    let signer = wallet.apple.as_ref().ok_or_else(|| anyhow::anyhow!("Apple Wallet is not configured"))?;
+
+    let mut files: BTreeMap<&str, Vec<u8>> = BTreeMap::new();
+    files.insert("pass.json", serde_json::to_vec(&pass_json(signer, &wallet.organization_name, event, ticket, qr_payload))?);
+    for (name, size) in ICON_SIZES {
+        files.insert(name, render_icon(size, accent_rgb(event))?);
+    }
+
+    let manifest: BTreeMap<&str, String> = files
+        .iter()
+        .map(|(name, bytes)| {
+            let digest = openssl::sha::sha1(bytes);
+            (*name, digest.iter().map(|b| format!("{:02x}", b)).collect())
+        })
+        .collect();
+    let manifest = serde_json::to_vec(&manifest)?;
+
+    let mut chain = Stack::new()?;
+    if let Some(wwdr) = &signer.wwdr {
+        chain.push(wwdr.clone())?;
+    }
+    let signature = Pkcs7::sign(
+        &signer.certificate,
+        &signer.key,
+        &chain,
+        &manifest,
+        Pkcs7Flags::DETACHED | Pkcs7Flags::BINARY,
+    )?
+    .to_der()?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, bytes) in files.iter().map(|(name, bytes)| (*name, bytes.as_slice()))
+        .chain([("manifest.json", manifest.as_slice()), ("signature", signature.as_slice())])
+    {
+        zip.start_file(name, options)?;
+        zip.write_all(bytes)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Link "Save to Google Wallet" berisi JWT bertanda tangan dengan class & object tiket.
+/// `origin` adalah `PUBLIC_BASE_URL`, domain yang boleh menampilkan tombol simpan.
+pub fn google_save_url(origin: &str, wallet: &WalletPasses, event: &Event, ticket: &Ticket, qr_payload: &str) -> Result<String> {
+    let issuer = wallet.google.as_ref().ok_or_else(|| anyhow::anyhow!("Google Wallet is not configured"))?;
+    let event_id = event.id.map(|id| id.to_hex()).unwrap_or_default();
+    let ticket_id = ticket.id.map(|id| id.to_hex()).unwrap_or_default();
+    let class_id = format!("{}.event-{}", issuer.issuer_id, event_id);
+    let (r, g, b) = accent_rgb(event);
+
+    let claims = json!({
+        "iss": issuer.client_email,
+        "aud": "google",
+        "typ": "savetowallet",
+        "iat": chrono::Utc::now().timestamp(),
+        "origins": [origin],
+        "payload": {
+            "eventTicketClasses": [{
+                "id": class_id,
+                "issuerName": wallet.organization_name,
+                "reviewStatus": "UNDER_REVIEW",
+                "eventName": {"defaultValue": {"language": "id", "value": event.name}},
+                "venue": {
+                    "name": {"defaultValue": {"language": "id", "value": event.location}},
+                    "address": {"defaultValue": {"language": "id", "value": event.location}},
+                },
+                "dateTime": {"start": event.local_date().to_rfc3339()},
+                "hexBackgroundColor": format!("#{:02x}{:02x}{:02x}", r, g, b),
+            }],
+            "eventTicketObjects": [{
+                "id": format!("{}.ticket-{}", issuer.issuer_id, ticket_id),
+                "classId": class_id,
+                "state": "ACTIVE",
+                "ticketHolderName": ticket.attendee_name,
+                "ticketNumber": ticket.code,
+                "ticketType": {"defaultValue": {"language": "id", "value": ticket.tier}},
+                "barcode": {"type": "QR_CODE", "value": qr_payload, "alternateText": ticket.code},
+            }],
+        },
+    });
+
+    let token = encode(&Header::new(Algorithm::RS256), &claims, &issuer.key)?;
+    Ok(format!("{}/{}", GOOGLE_SAVE_URL, token))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use jsonwebtoken::{decode, DecodingKey, Validation};
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::{BigNum, MsbOption};
+    use openssl::hash::MessageDigest;
+    use openssl::rsa::Rsa;
+    use openssl::x509::store::X509StoreBuilder;
+    use openssl::x509::X509NameBuilder;
+    use zip::ZipArchive;
+
+    use super::*;
+
+    fn rsa_key() -> PKey<Private> {
+        PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap()
+    }
+
+    fn self_signed_cert(key: &PKey<Private>) -> X509 {
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "pass.test.ticketing").unwrap();
+        let name = name.build();
+        let mut serial = BigNum::new().unwrap();
+        serial.rand(64, MsbOption::MAYBE_ZERO, false).unwrap();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_serial_number(&serial.to_asn1_integer().unwrap()).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(key).unwrap();
+        builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        builder.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        builder.sign(key, MessageDigest::sha256()).unwrap();
+        builder.build()
+    }
+
+    fn sample_event() -> Event {
+        serde_json::from_value(json!({
+            "_id": {"$oid": "65f1a2b3c4d5e6f7a8b9c0d1"},
+            "name": "Konser Musim Panas",
+            "description": "",
+            "date": "2025-07-01T12:00:00Z",
+            "location": "Jakarta",
+            "price": 150000.0,
+            "total_tickets": 100,
+            "available_tickets": 100,
+        }))
+        .unwrap()
+    }
+
+    fn sample_ticket() -> Ticket {
+        serde_json::from_value(json!({
+            "_id": {"$oid": "65f1a2b3c4d5e6f7a8b9c0d2"},
+            "order_id": {"$oid": "65f1a2b3c4d5e6f7a8b9c0d3"},
+            "event_id": {"$oid": "65f1a2b3c4d5e6f7a8b9c0d1"},
+            "code": "TCK-ABC123",
+            "attendee_name": "Budi",
+            "tier": "regular",
+            "status": "valid",
+            "created_at": "2025-06-01T08:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn wallet(apple: Option<ApplePassSigner>, google: Option<GoogleWalletIssuer>) -> WalletPasses {
+        WalletPasses { organization_name: "Ticketing Test".to_string(), apple, google }
+    }
+
+    fn read_entries(pkpass: &[u8]) -> BTreeMap<String, Vec<u8>> {
+        let mut archive = ZipArchive::new(Cursor::new(pkpass)).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut bytes = Vec::new();
+                file.read_to_end(&mut bytes).unwrap();
+                (file.name().to_string(), bytes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pkpass_manifest_and_signature_verify() {
+        let key = rsa_key();
+        let certificate = self_signed_cert(&key);
+        let signer = ApplePassSigner {
+            pass_type_id: "pass.test.ticketing".to_string(),
+            team_id: "TEAM123456".to_string(),
+            certificate: certificate.clone(),
+            key,
+            wwdr: None,
+        };
+        let pkpass = build_pkpass(&wallet(Some(signer), None), &sample_event(), &sample_ticket(), "QR-PAYLOAD").unwrap();
+        let entries = read_entries(&pkpass);
+
+        let manifest_bytes = &entries["manifest.json"];
+        let manifest: BTreeMap<String, String> = serde_json::from_slice(manifest_bytes).unwrap();
+        let signed_files: Vec<&String> = entries.keys().filter(|name| *name != "manifest.json" && *name != "signature").collect();
+        assert_eq!(manifest.keys().collect::<Vec<_>>(), signed_files);
+        for (name, digest) in &manifest {
+            let expected: String = openssl::sha::sha1(&entries[name]).iter().map(|b| format!("{:02x}", b)).collect();
+            assert_eq!(digest, &expected, "SHA-1 mismatch for {}", name);
+        }
+
+        let pass: serde_json::Value = serde_json::from_slice(&entries["pass.json"]).unwrap();
+        assert_eq!(pass["barcodes"][0]["message"], "QR-PAYLOAD");
+
+        let signature = Pkcs7::from_der(&entries["signature"]).unwrap();
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(certificate).unwrap();
+        let store = store.build();
+        let certs = Stack::new().unwrap();
+        signature
+            .verify(&certs, &store, Some(manifest_bytes), None, Pkcs7Flags::BINARY)
+            .expect("signature must verify over manifest.json");
+
+        let mut tampered = manifest_bytes.clone();
+        tampered[2] ^= 1;
+        assert!(signature.verify(&certs, &store, Some(&tampered), None, Pkcs7Flags::BINARY).is_err());
+    }
+
+    #[test]
+    fn google_save_url_carries_verifiable_jwt() {
+        let key = rsa_key();
+        let issuer = GoogleWalletIssuer {
+            issuer_id: "3388000000012345678".to_string(),
+            client_email: "wallet@test.iam.gserviceaccount.com".to_string(),
+            key: EncodingKey::from_rsa_pem(&key.private_key_to_pem_pkcs8().unwrap()).unwrap(),
+        };
+        let url = google_save_url("https://tickets.example", &wallet(None, Some(issuer)), &sample_event(), &sample_ticket(), "QR-PAYLOAD").unwrap();
+        let token = url.strip_prefix(&format!("{}/", GOOGLE_SAVE_URL)).unwrap();
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&["google"]);
+        validation.required_spec_claims.clear();
+        let decoding_key = DecodingKey::from_rsa_pem(&key.public_key_to_pem().unwrap()).unwrap();
+        let claims = decode::<serde_json::Value>(token, &decoding_key, &validation).unwrap().claims;
+
+        assert_eq!(claims["iss"], "wallet@test.iam.gserviceaccount.com");
+        assert_eq!(claims["origins"], json!(["https://tickets.example"]));
+        let object = &claims["payload"]["eventTicketObjects"][0];
+        assert_eq!(object["id"], "3388000000012345678.ticket-65f1a2b3c4d5e6f7a8b9c0d2");
+        assert_eq!(object["classId"], "3388000000012345678.event-65f1a2b3c4d5e6f7a8b9c0d1");
+        assert_eq!(object["barcode"]["value"], "QR-PAYLOAD");
+
+        let other_key = DecodingKey::from_rsa_pem(&rsa_key().public_key_to_pem().unwrap()).unwrap();
+        assert!(decode::<serde_json::Value>(token, &other_key, &validation).is_err());
+    }
+}